@@ -2,6 +2,9 @@
 #![warn(clippy::pedantic)]
 #![warn(clippy::cargo)]
 #![allow(clippy::unused_async)]
+#![allow(clippy::multiple_crate_versions)]
+
+use std::borrow::Cow;
 
 use argon2::Error as ArgonError;
 use reqwest::Error as ReqwestError;
@@ -18,12 +21,32 @@ use warp::{
 pub enum Error {
     ParseError(std::num::ParseIntError),
     MissingParameters,
+    InvalidInput(String),
     WrongPassword,
     CannotDecryptToken,
     Unauthorized,
+    NotFound,
+    PreconditionFailed,
+    /// A row with the same key exists, names the violated constraint
+    UniqueViolation(Option<String>),
+    /// A referenced row does not exist, names the violated constraint
+    ForeignKeyViolation(Option<String>),
+    /// No database connection became free in time
+    PoolExhausted,
+    ProfaneContent(Vec<String>),
+    ModerationUnavailable,
     ArgonLibraryError(ArgonError),
     DatabaseQueryError(sqlx::Error),
     MigrationError(sqlx::migrate::MigrateError),
+    WordlistError(std::io::Error),
+    /// A seed fixture cannot be read or refers to something missing
+    FixtureError(String),
+    /// An export cannot be written or an import cannot be read
+    TransferError(std::io::Error),
+    /// A request body is larger than the given number of bytes
+    BodyTooLarge(usize),
+    /// A client stopped sending its request body
+    BodyTimeout,
     ReqwestAPIError(ReqwestError),
     MiddlewareReqwestAPIError(MiddlewareReqwestError),
     ClientError(APILayerError),
@@ -44,52 +67,108 @@ impl std::fmt::Display for APILayerError {
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match &*self {
-            Error::ParseError(ref err) => write!(f, "Cannot parse parameter: {}", err),
+        match self {
+            Error::ParseError(ref err) => write!(f, "Cannot parse parameter: {err}"),
             Error::MissingParameters => write!(f, "Missing parameter"),
+            Error::InvalidInput(reason) => write!(f, "Invalid input: {reason}"),
             Error::WrongPassword => write!(f, "Wrong password"),
             Error::CannotDecryptToken => write!(f, "Cannot decrypt error"),
             Error::Unauthorized => write!(f, "No permission to change the underlying resource"),
+            Error::NotFound => write!(f, "Requested resource not found"),
+            Error::PreconditionFailed => write!(f, "Resource was modified in the meantime"),
+            Error::UniqueViolation(constraint) => {
+                write!(
+                    f,
+                    "Resource already exists ({})",
+                    constraint_name(constraint.as_deref())
+                )
+            }
+            Error::ForeignKeyViolation(constraint) => write!(
+                f,
+                "Referenced resource does not exist ({})",
+                constraint_name(constraint.as_deref())
+            ),
+            Error::PoolExhausted => write!(f, "No database connection available"),
+            Error::ProfaneContent(fields) => {
+                write!(f, "Content contains profanity: {}", fields.join(", "))
+            }
+            Error::ModerationUnavailable => write!(f, "Content moderation is unavailable"),
             Error::ArgonLibraryError(_) => write!(f, "Cannot verifiy password"),
             Error::DatabaseQueryError(_) => write!(f, "Cannot update, invalid data"),
             Error::MigrationError(_) => write!(f, "Cannot migrate data"),
-            Error::ReqwestAPIError(err) => write!(f, "External API error: {}", err),
-            Error::MiddlewareReqwestAPIError(err) => write!(f, "External API error: {}", err),
-            Error::ClientError(err) => write!(f, "External Client error: {}", err),
-            Error::ServerError(err) => write!(f, "External Server error: {}", err),
+            Error::WordlistError(err) => write!(f, "Cannot read wordlist: {err}"),
+            Error::FixtureError(err) => write!(f, "Cannot load fixture: {err}"),
+            Error::TransferError(err) => write!(f, "Cannot transfer records: {err}"),
+            Error::BodyTooLarge(limit) => write!(f, "Request body is larger than {limit} bytes"),
+            Error::BodyTimeout => write!(f, "Request body was not sent in time"),
+            Error::ReqwestAPIError(err) => write!(f, "External API error: {err}"),
+            Error::MiddlewareReqwestAPIError(err) => write!(f, "External API error: {err}"),
+            Error::ClientError(err) => write!(f, "External Client error: {err}"),
+            Error::ServerError(err) => write!(f, "External Server error: {err}"),
         }
     }
 }
 
+fn constraint_name(constraint: Option<&str>) -> &str {
+    constraint.unwrap_or("unknown constraint")
+}
+
 impl Reject for Error {}
 impl Reject for APILayerError {}
 
-const DUPLICATE_KEY: u32 = 23505;
+/// SQLSTATE of a `unique_violation`
+const UNIQUE_VIOLATION: &str = "23505";
+/// SQLSTATE of a `foreign_key_violation`
+const FOREIGN_KEY_VIOLATION: &str = "23503";
+
+/// Maps the errors callers can react to onto their own variant,
+/// everything else stays a `DatabaseQueryError`
+impl From<sqlx::Error> for Error {
+    fn from(error: sqlx::Error) -> Self {
+        let (code, constraint) = match error.as_database_error() {
+            Some(db_error) => (
+                db_error.code().map(Cow::into_owned),
+                db_error.constraint().map(str::to_string),
+            ),
+            None => (None, None),
+        };
+
+        match (&error, code.as_deref()) {
+            (sqlx::Error::RowNotFound, _) => Error::NotFound,
+            (sqlx::Error::PoolTimedOut, _) => Error::PoolExhausted,
+            (_, Some(UNIQUE_VIOLATION)) => Error::UniqueViolation(constraint),
+            (_, Some(FOREIGN_KEY_VIOLATION)) => Error::ForeignKeyViolation(constraint),
+            _ => Error::DatabaseQueryError(error),
+        }
+    }
+}
 
 #[instrument]
 pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
     if let Some(crate::Error::DatabaseQueryError(e)) = r.find() {
-        event!(Level::ERROR, "Database query error");
-
-        match e {
-            sqlx::Error::Database(err) => {
-                if err.code().unwrap().parse::<u32>().unwrap() == DUPLICATE_KEY {
-                    Ok(warp::reply::with_status(
-                        "Account already exsists".to_string(),
-                        StatusCode::UNPROCESSABLE_ENTITY,
-                    ))
-                } else {
-                    Ok(warp::reply::with_status(
-                        "Cannot update data".to_string(),
-                        StatusCode::UNPROCESSABLE_ENTITY,
-                    ))
-                }
-            }
-            _ => Ok(warp::reply::with_status(
-                "Cannot update data".to_string(),
-                StatusCode::UNPROCESSABLE_ENTITY,
-            )),
-        }
+        event!(Level::ERROR, "Database query error: {}", e);
+        Ok(warp::reply::with_status(
+            "Cannot update data".to_string(),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ))
+    } else if let Some(crate::Error::UniqueViolation(constraint)) = r.find() {
+        event!(Level::WARN, constraint = ?constraint, "Resource already exists");
+        Ok(warp::reply::with_status(
+            "Resource already exists".to_string(),
+            StatusCode::CONFLICT,
+        ))
+    } else if let Some(crate::Error::ForeignKeyViolation(constraint)) = r.find() {
+        event!(Level::WARN, constraint = ?constraint, "Referenced resource does not exist");
+        Ok(warp::reply::with_status(
+            "Referenced resource does not exist".to_string(),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ))
+    } else if let Some(crate::Error::PoolExhausted) = r.find() {
+        event!(Level::ERROR, "No database connection available");
+        Ok(warp::reply::with_status(
+            "Service is busy, try again later".to_string(),
+            StatusCode::SERVICE_UNAVAILABLE,
+        ))
     } else if let Some(crate::Error::ReqwestAPIError(e)) = r.find() {
         event!(Level::ERROR, "{}", e);
         Ok(warp::reply::with_status(
@@ -102,6 +181,36 @@ pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
             "No permission to change underlying resource".to_string(),
             StatusCode::UNAUTHORIZED,
         ))
+    } else if let Some(crate::Error::NotFound) = r.find() {
+        event!(Level::WARN, "Requested resource was not found");
+        Ok(warp::reply::with_status(
+            "Requested resource not found".to_string(),
+            StatusCode::NOT_FOUND,
+        ))
+    } else if let Some(crate::Error::PreconditionFailed) = r.find() {
+        event!(Level::WARN, "Resource was modified in the meantime");
+        Ok(warp::reply::with_status(
+            "Resource was modified in the meantime".to_string(),
+            StatusCode::PRECONDITION_FAILED,
+        ))
+    } else if let Some(error @ crate::Error::BodyTooLarge(_)) = r.find() {
+        event!(Level::WARN, "{}", error);
+        Ok(warp::reply::with_status(
+            error.to_string(),
+            StatusCode::PAYLOAD_TOO_LARGE,
+        ))
+    } else if let Some(crate::Error::BodyTimeout) = r.find() {
+        event!(Level::WARN, "Request body was not sent in time");
+        Ok(warp::reply::with_status(
+            "Request body was not sent in time".to_string(),
+            StatusCode::REQUEST_TIMEOUT,
+        ))
+    } else if let Some(crate::Error::ModerationUnavailable) = r.find() {
+        event!(Level::ERROR, "Content moderation is unavailable");
+        Ok(warp::reply::with_status(
+            "Content moderation is unavailable, try again later".to_string(),
+            StatusCode::SERVICE_UNAVAILABLE,
+        ))
     } else if let Some(crate::Error::WrongPassword) = r.find() {
         event!(Level::ERROR, "Entered wrong password");
         Ok(warp::reply::with_status(
@@ -152,3 +261,69 @@ pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
         ))
     }
 }
+
+#[cfg(test)]
+mod handle_errors_tests {
+    use super::{return_error, Error, StatusCode};
+    use warp::Reply;
+
+    #[tokio::test]
+    async fn not_found_is_404() {
+        let reply = return_error(warp::reject::custom(Error::NotFound))
+            .await
+            .unwrap();
+
+        assert_eq!(reply.into_response().status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn classify_sqlx_errors() {
+        assert!(matches!(
+            Error::from(sqlx::Error::RowNotFound),
+            Error::NotFound
+        ));
+        assert!(matches!(
+            Error::from(sqlx::Error::PoolTimedOut),
+            Error::PoolExhausted
+        ));
+        // Errors which did not come from the database have no code
+        // or constraint, they must not panic either
+        assert!(matches!(
+            Error::from(sqlx::Error::Protocol("unexpected message".to_string())),
+            Error::DatabaseQueryError(_)
+        ));
+
+        let reply = return_error(warp::reject::custom(Error::from(sqlx::Error::PoolTimedOut)))
+            .await
+            .unwrap();
+        assert_eq!(
+            reply.into_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
+    #[tokio::test]
+    async fn unread_bodies_are_413_and_408() {
+        let reply = return_error(warp::reject::custom(Error::BodyTooLarge(10)))
+            .await
+            .unwrap();
+        assert_eq!(
+            reply.into_response().status(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+
+        let reply = return_error(warp::reject::custom(Error::BodyTimeout))
+            .await
+            .unwrap();
+        assert_eq!(reply.into_response().status(), StatusCode::REQUEST_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn unauthorized_is_401() {
+        let reply = return_error(warp::reject::custom(Error::Unauthorized))
+            .await
+            .unwrap();
+
+        assert_eq!(reply.into_response().status(), StatusCode::UNAUTHORIZED);
+    }
+}
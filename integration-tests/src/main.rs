@@ -4,8 +4,10 @@ use std::process::Command;
 use futures_util::future::FutureExt;
 
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use warp_rest_api::{config, handle_errors, oneshot, setup_store};
+use sqlx::PgPool;
+use warp_rest_api::{
+    config, handle_errors, load_fixture, oneshot, seed::Seeded, setup_moderation, setup_store,
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct User {
@@ -19,6 +21,13 @@ struct Question {
     content: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Answer {
+    #[serde(rename = "questionId")]
+    question_id: String,
+    content: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct QuestionAnswer {
     id: i32,
@@ -33,7 +42,7 @@ struct Token(String);
 #[tokio::main]
 async fn main() -> Result<(), handle_errors::Error> {
     dotenv::dotenv().ok();
-    let config = config::Config::new().expect("Config can't be set");
+    let mut config = config::Config::new().expect("Config can't be set");
 
     let s = Command::new("sqlx")
         .arg("database")
@@ -63,11 +72,43 @@ async fn main() -> Result<(), handle_errors::Error> {
     // Exdcute DB commands to drop and create a new test database
     io::stdout().write_all(&s.stderr).unwrap();
 
+    // An empty replica, so it can be told which reads it serves
+    let replica_db = format!("{}_replica", config.db_name);
+    for action in ["drop", "create"] {
+        let mut command = Command::new("sqlx");
+        command
+            .arg("database")
+            .arg(action)
+            .arg("--database-url")
+            .arg(format!(
+                "postgres://{}:{}/{}",
+                config.db_host, config.db_port, replica_db
+            ));
+        if action == "drop" {
+            command.arg("-y");
+        }
+        let s = command.output().expect("sqlx command failed to start");
+        io::stdout().write_all(&s.stderr).unwrap();
+    }
+    let replica_url = format!(
+        "postgres://{}:{}@{}:{}/{}",
+        config.db_user, config.db_password, config.db_host, config.db_port, replica_db
+    );
+    let replica = PgPool::connect(&replica_url)
+        .await
+        .map_err(handle_errors::Error::DatabaseQueryError)?;
+    sqlx::migrate!("../migrations")
+        .run(&replica)
+        .await
+        .map_err(handle_errors::Error::MigrationError)?;
+    config.read_replica_url = Some(replica_url);
+
     // set up a new store instance with a db connection pool
     let store = setup_store(&config).await?;
+    let moderation = setup_moderation(&config)?;
 
     // start the server and listen for a sender signal to shut it down
-    let handler = oneshot(store).await;
+    let handler = oneshot(store.clone(), moderation).await;
 
     // create a test user to use throughout the tests
     let u = User {
@@ -102,7 +143,61 @@ async fn main() -> Result<(), handle_errors::Error> {
     }
 
     print!("Running post_question...");
-    match std::panic::AssertUnwindSafe(post_question(token))
+    match std::panic::AssertUnwindSafe(post_question(token.clone()))
+        .catch_unwind()
+        .await
+    {
+        Ok(_) => println!("✓"),
+        Err(_) => {
+            let _ = handler.sender.send(1);
+            std::process::exit(1);
+        }
+    }
+
+    print!("Running post_answer...");
+    match std::panic::AssertUnwindSafe(post_answer(token.clone()))
+        .catch_unwind()
+        .await
+    {
+        Ok(_) => println!("✓"),
+        Err(_) => {
+            let _ = handler.sender.send(1);
+            std::process::exit(1);
+        }
+    }
+
+    print!("Running delete_question...");
+    match std::panic::AssertUnwindSafe(delete_question(token))
+        .catch_unwind()
+        .await
+    {
+        Ok(_) => println!("✓"),
+        Err(_) => {
+            let _ = handler.sender.send(1);
+            std::process::exit(1);
+        }
+    }
+
+    print!("Running seed_fixture...");
+    let fixture = std::path::Path::new(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../fixtures/demo.yaml"
+    ));
+    let first = load_fixture(&store, fixture).await?;
+    let second = load_fixture(&store, fixture).await?;
+    match std::panic::AssertUnwindSafe(seeded_login(first, second))
+        .catch_unwind()
+        .await
+    {
+        Ok(_) => println!("✓"),
+        Err(_) => {
+            let _ = handler.sender.send(1);
+            std::process::exit(1);
+        }
+    }
+
+    print!("Running read_replica...");
+    match std::panic::AssertUnwindSafe(read_replica(replica))
         .catch_unwind()
         .await
     {
@@ -126,7 +221,7 @@ async fn register_new_user(user: &User) {
         .send()
         .await
         .unwrap()
-        .json::<Value>()
+        .text()
         .await;
 
     assert_eq!(res.unwrap(), "Account added".to_string());
@@ -167,3 +262,123 @@ async fn post_question(token: Token) {
     assert_eq!(res.id, 1);
     assert_eq!(res.title, q.title);
 }
+
+async fn post_answer(token: Token) {
+    let a = Answer {
+        question_id: "1".to_string(),
+        content: "Like this".to_string(),
+    };
+
+    let client = reqwest::Client::new();
+    let res = client
+        .post("http://localhost:3030/answers")
+        .header("Authorization", token.0)
+        .json(&a)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), 200);
+}
+
+async fn delete_question(token: Token) {
+    let client = reqwest::Client::new();
+
+    // The question has an answer, which must not block the delete
+    let res = client
+        .delete("http://localhost:3030/questions/1")
+        .header("Authorization", token.0.clone())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    let res = client
+        .get("http://localhost:3030/questions/1")
+        .send()
+        .await
+        .unwrap();
+    assert_ne!(res.status(), 200);
+
+    // Deleted questions take no answers
+    let res = client
+        .post("http://localhost:3030/answers")
+        .header("Authorization", token.0.clone())
+        .json(&Answer {
+            question_id: "1".to_string(),
+            content: "Too late".to_string(),
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+
+    // Deleting it a second time has nothing left to delete
+    let res = client
+        .delete("http://localhost:3030/questions/1")
+        .header("Authorization", token.0.clone())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+
+    let res = client
+        .delete("http://localhost:3030/questions/999")
+        .header("Authorization", token.0)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+}
+
+async fn seeded_login(first: Seeded, second: Seeded) {
+    assert_eq!(first.accounts, 2);
+    assert_eq!(first.questions, 2);
+    assert_eq!(first.answers, 2);
+    // Loading it again finds everything in place
+    assert_eq!(second, Seeded::default());
+
+    login(User {
+        email: "alice@email.com".to_string(),
+        password: "password".to_string(),
+    })
+    .await;
+}
+
+async fn read_replica(replica: PgPool) {
+    let client = reqwest::Client::new();
+
+    // The listing comes from the replica, which has no questions
+    let res = client
+        .get("http://localhost:3030/questions")
+        .send()
+        .await
+        .unwrap()
+        .json::<Vec<QuestionAnswer>>()
+        .await
+        .unwrap();
+    assert!(res.is_empty());
+
+    // A seeded question the replica does not have yet is read from the primary
+    let res = client
+        .get("http://localhost:3030/questions/2")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    // So is everything once the replica fails
+    sqlx::query("DROP TABLE questions CASCADE")
+        .execute(&replica)
+        .await
+        .unwrap();
+    let res = client
+        .get("http://localhost:3030/questions")
+        .send()
+        .await
+        .unwrap()
+        .json::<Vec<QuestionAnswer>>()
+        .await
+        .unwrap();
+    assert_eq!(res.len(), 2);
+}
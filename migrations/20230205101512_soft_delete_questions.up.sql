@@ -0,0 +1,4 @@
+ALTER TABLE
+    questions
+ADD
+    COLUMN deleted_at TIMESTAMP;
\ No newline at end of file
@@ -0,0 +1,4 @@
+ALTER TABLE
+    answers DROP CONSTRAINT answers_corresponding_question_fkey,
+ADD
+    CONSTRAINT answers_corresponding_question_fkey FOREIGN KEY (corresponding_question) REFERENCES questions;
\ No newline at end of file
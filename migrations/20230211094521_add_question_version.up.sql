@@ -0,0 +1,4 @@
+ALTER TABLE
+    questions
+ADD
+    COLUMN version integer NOT NULL DEFAULT 1;
\ No newline at end of file
@@ -0,0 +1,5 @@
+ALTER TABLE
+    accounts DROP COLUMN display_name;
+
+ALTER TABLE
+    questions DROP COLUMN updated_on;
\ No newline at end of file
@@ -0,0 +1,14 @@
+ALTER TABLE
+    questions
+ADD
+    COLUMN updated_on TIMESTAMP NOT NULL DEFAULT NOW();
+
+UPDATE
+    questions
+SET
+    updated_on = created_on;
+
+ALTER TABLE
+    accounts
+ADD
+    COLUMN display_name VARCHAR(255);
\ No newline at end of file
@@ -0,0 +1,4 @@
+ALTER TABLE
+    accounts DROP COLUMN bio,
+    DROP COLUMN avatar_url,
+    DROP COLUMN created_on;
\ No newline at end of file
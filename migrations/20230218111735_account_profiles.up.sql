@@ -0,0 +1,8 @@
+ALTER TABLE
+    accounts
+ADD
+    COLUMN bio TEXT,
+ADD
+    COLUMN avatar_url VARCHAR(2048),
+ADD
+    COLUMN created_on TIMESTAMP NOT NULL DEFAULT NOW();
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE
+    accounts DROP COLUMN reputation;
\ No newline at end of file
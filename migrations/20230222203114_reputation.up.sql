@@ -0,0 +1,34 @@
+ALTER TABLE
+    accounts
+ADD
+    COLUMN reputation BIGINT NOT NULL DEFAULT 0;
+
+-- Backfill with the weights of the default `ReputationRules`
+UPDATE
+    accounts
+SET
+    reputation = 5 * (
+        SELECT
+            COUNT(*)
+        FROM
+            questions
+        WHERE
+            questions.account_id = accounts.id
+            AND questions.deleted_at IS NULL
+    ) + 2 * (
+        SELECT
+            COUNT(*)
+        FROM
+            answers
+        WHERE
+            answers.account_id = accounts.id
+    ) + 8 * (
+        SELECT
+            COUNT(*)
+        FROM
+            answers
+            JOIN questions ON questions.id = answers.corresponding_question
+        WHERE
+            answers.account_id = accounts.id
+            AND questions.deleted_at IS NULL
+    );
\ No newline at end of file
@@ -0,0 +1,9 @@
+ALTER TABLE
+    questions
+ADD
+    COLUMN moderation_status TEXT NOT NULL DEFAULT 'approved';
+
+ALTER TABLE
+    answers
+ADD
+    COLUMN moderation_status TEXT NOT NULL DEFAULT 'approved';
\ No newline at end of file
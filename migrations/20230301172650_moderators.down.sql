@@ -0,0 +1,2 @@
+ALTER TABLE
+    accounts DROP COLUMN is_moderator;
\ No newline at end of file
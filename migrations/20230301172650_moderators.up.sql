@@ -0,0 +1,4 @@
+ALTER TABLE
+    accounts
+ADD
+    COLUMN is_moderator BOOLEAN NOT NULL DEFAULT FALSE;
\ No newline at end of file
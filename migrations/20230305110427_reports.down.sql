@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS reports;
+
+ALTER TABLE
+    accounts DROP CONSTRAINT accounts_id_key;
\ No newline at end of file
@@ -0,0 +1,28 @@
+-- Reports refer to accounts by id, which is not yet the key of accounts
+ALTER TABLE
+    accounts
+ADD
+    CONSTRAINT accounts_id_key UNIQUE (id);
+
+CREATE TABLE IF NOT EXISTS reports (
+    id serial PRIMARY KEY,
+    reporter_id integer NOT NULL REFERENCES accounts (id),
+    question_id integer REFERENCES questions ON DELETE CASCADE,
+    answer_id integer REFERENCES answers ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    note TEXT,
+    created_on TIMESTAMP NOT NULL DEFAULT NOW(),
+    resolved_on TIMESTAMP,
+    resolved_by integer REFERENCES accounts (id),
+    resolution TEXT,
+    CHECK ((question_id IS NULL) <> (answer_id IS NULL))
+);
+
+-- Every account can report a post only once
+CREATE UNIQUE INDEX reports_question_reporter ON reports (question_id, reporter_id)
+WHERE
+    question_id IS NOT NULL;
+
+CREATE UNIQUE INDEX reports_answer_reporter ON reports (answer_id, reporter_id)
+WHERE
+    answer_id IS NOT NULL;
\ No newline at end of file
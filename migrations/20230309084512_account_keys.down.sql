@@ -0,0 +1,38 @@
+CREATE SEQUENCE answers_account_id_seq OWNED BY answers.account_id;
+
+ALTER TABLE
+    answers DROP CONSTRAINT answers_account_id_fkey,
+ALTER COLUMN
+    account_id
+SET
+    DEFAULT nextval('answers_account_id_seq');
+
+CREATE SEQUENCE questions_account_id_seq OWNED BY questions.account_id;
+
+ALTER TABLE
+    questions DROP CONSTRAINT questions_account_id_fkey,
+ALTER COLUMN
+    account_id
+SET
+    DEFAULT nextval('questions_account_id_seq');
+
+DROP INDEX accounts_email_lower;
+
+-- Merged and placeholder accounts stay as they are
+ALTER TABLE
+    reports DROP CONSTRAINT reports_reporter_id_fkey,
+    DROP CONSTRAINT reports_resolved_by_fkey;
+
+ALTER TABLE
+    accounts DROP CONSTRAINT accounts_pkey,
+ADD
+    CONSTRAINT accounts_pkey PRIMARY KEY (email),
+ADD
+    CONSTRAINT accounts_id_key UNIQUE (id);
+
+ALTER TABLE
+    reports
+ADD
+    CONSTRAINT reports_reporter_id_fkey FOREIGN KEY (reporter_id) REFERENCES accounts (id),
+ADD
+    CONSTRAINT reports_resolved_by_fkey FOREIGN KEY (resolved_by) REFERENCES accounts (id);
@@ -0,0 +1,180 @@
+-- The same address could sign up again in another case while accounts
+-- were keyed by the email as written. Such accounts are merged into the
+-- oldest one, which keeps its email, password and profile. Ids are
+-- already distinct, `accounts_id_key` made sure of that.
+CREATE TEMPORARY TABLE merged_accounts AS
+SELECT
+    id,
+    MIN(id) OVER (PARTITION BY lower(email)) AS into_id
+FROM
+    accounts;
+
+DELETE FROM
+    merged_accounts
+WHERE
+    id = into_id;
+
+UPDATE
+    accounts
+SET
+    reputation = accounts.reputation + merged.reputation,
+    is_moderator = accounts.is_moderator
+    OR merged.is_moderator
+FROM
+    (
+        SELECT
+            merged_accounts.into_id,
+            SUM(accounts.reputation) AS reputation,
+            bool_or(accounts.is_moderator) AS is_moderator
+        FROM
+            merged_accounts
+            JOIN accounts ON accounts.id = merged_accounts.id
+        GROUP BY
+            merged_accounts.into_id
+    ) AS merged
+WHERE
+    accounts.id = merged.into_id;
+
+UPDATE
+    questions
+SET
+    account_id = merged_accounts.into_id
+FROM
+    merged_accounts
+WHERE
+    questions.account_id = merged_accounts.id;
+
+UPDATE
+    answers
+SET
+    account_id = merged_accounts.into_id
+FROM
+    merged_accounts
+WHERE
+    answers.account_id = merged_accounts.id;
+
+-- The merged account reports every post only once, keeping the first report
+DELETE FROM
+    reports
+WHERE
+    id IN (
+        SELECT
+            id
+        FROM
+            (
+                SELECT
+                    reports.id,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY COALESCE(merged_accounts.into_id, reports.reporter_id),
+                        reports.question_id,
+                        reports.answer_id
+                        ORDER BY
+                            reports.id
+                    ) AS n
+                FROM
+                    reports
+                    LEFT JOIN merged_accounts ON merged_accounts.id = reports.reporter_id
+            ) AS ranked
+        WHERE
+            n > 1
+    );
+
+UPDATE
+    reports
+SET
+    reporter_id = merged_accounts.into_id
+FROM
+    merged_accounts
+WHERE
+    reports.reporter_id = merged_accounts.id;
+
+UPDATE
+    reports
+SET
+    resolved_by = merged_accounts.into_id
+FROM
+    merged_accounts
+WHERE
+    reports.resolved_by = merged_accounts.id;
+
+DELETE FROM
+    accounts USING merged_accounts
+WHERE
+    accounts.id = merged_accounts.id;
+
+DROP TABLE merged_accounts;
+
+-- The `serial` author columns made up ids no account has. Their posts
+-- get a placeholder account, whose empty password never matches.
+INSERT INTO
+    accounts (id, email, password)
+SELECT
+    account_id,
+    'account-' || account_id || '@invalid',
+    ''
+FROM
+    (
+        SELECT
+            account_id
+        FROM
+            questions
+        UNION
+        SELECT
+            account_id
+        FROM
+            answers
+    ) AS authors
+WHERE
+    account_id NOT IN (
+        SELECT
+            id
+        FROM
+            accounts
+    );
+
+-- Ids are unique from now on, new ones must not collide with old ones
+SELECT
+    setval(
+        pg_get_serial_sequence('accounts', 'id'),
+        GREATEST((SELECT MAX(id) FROM accounts), 1),
+        (SELECT COUNT(*) > 0 FROM accounts)
+    );
+
+-- The reports referred to `accounts_id_key`, which the primary key replaces
+ALTER TABLE
+    reports DROP CONSTRAINT reports_reporter_id_fkey,
+    DROP CONSTRAINT reports_resolved_by_fkey;
+
+ALTER TABLE
+    accounts DROP CONSTRAINT accounts_id_key,
+    DROP CONSTRAINT accounts_pkey,
+ADD
+    CONSTRAINT accounts_pkey PRIMARY KEY (id);
+
+ALTER TABLE
+    reports
+ADD
+    CONSTRAINT reports_reporter_id_fkey FOREIGN KEY (reporter_id) REFERENCES accounts (id),
+ADD
+    CONSTRAINT reports_resolved_by_fkey FOREIGN KEY (resolved_by) REFERENCES accounts (id);
+
+CREATE UNIQUE INDEX accounts_email_lower ON accounts (lower(email));
+
+-- The authors were `serial` columns, which made up ids of their own
+ALTER TABLE
+    questions
+ALTER COLUMN
+    account_id DROP DEFAULT,
+ADD
+    CONSTRAINT questions_account_id_fkey FOREIGN KEY (account_id) REFERENCES accounts (id);
+
+DROP SEQUENCE IF EXISTS questions_account_id_seq;
+
+ALTER TABLE
+    answers
+ALTER COLUMN
+    account_id DROP DEFAULT,
+ADD
+    CONSTRAINT answers_account_id_fkey FOREIGN KEY (account_id) REFERENCES accounts (id);
+
+DROP SEQUENCE IF EXISTS answers_account_id_seq;
@@ -0,0 +1,25 @@
+ALTER TABLE
+    reports
+ALTER COLUMN
+    created_on TYPE TIMESTAMP USING created_on AT TIME ZONE 'UTC',
+ALTER COLUMN
+    resolved_on TYPE TIMESTAMP USING resolved_on AT TIME ZONE 'UTC';
+
+ALTER TABLE
+    accounts
+ALTER COLUMN
+    created_on TYPE TIMESTAMP USING created_on AT TIME ZONE 'UTC';
+
+ALTER TABLE
+    answers
+ALTER COLUMN
+    created_on TYPE TIMESTAMP USING created_on AT TIME ZONE 'UTC';
+
+ALTER TABLE
+    questions
+ALTER COLUMN
+    created_on TYPE TIMESTAMP USING created_on AT TIME ZONE 'UTC',
+ALTER COLUMN
+    updated_on TYPE TIMESTAMP USING updated_on AT TIME ZONE 'UTC',
+ALTER COLUMN
+    deleted_at TYPE TIMESTAMP USING deleted_at AT TIME ZONE 'UTC';
@@ -0,0 +1,47 @@
+-- TIMESTAMP keeps no time zone. NOW() filled the columns with the wall
+-- clock time of the session's TimeZone, which is UTC for every connection
+-- of the server, as sqlx sets it when connecting. So they are converted
+-- as UTC, explicitly, whatever the TimeZone of this session is. Rows a
+-- client with another TimeZone wrote are shifted by its offset, the ones
+-- this puts into the future are reported.
+DO $$
+DECLARE
+    future BIGINT;
+BEGIN
+    SELECT
+        (SELECT COUNT(*) FROM questions WHERE GREATEST(created_on, updated_on, deleted_at) > NOW() AT TIME ZONE 'UTC')
+        + (SELECT COUNT(*) FROM answers WHERE created_on > NOW() AT TIME ZONE 'UTC')
+        + (SELECT COUNT(*) FROM accounts WHERE created_on > NOW() AT TIME ZONE 'UTC')
+        + (SELECT COUNT(*) FROM reports WHERE GREATEST(created_on, resolved_on) > NOW() AT TIME ZONE 'UTC')
+    INTO future;
+
+    IF future > 0 THEN
+        RAISE WARNING '% rows have timestamps in the future read as UTC, they were written with a TimeZone other than UTC', future;
+    END IF;
+END $$;
+
+ALTER TABLE
+    questions
+ALTER COLUMN
+    created_on TYPE TIMESTAMPTZ USING created_on AT TIME ZONE 'UTC',
+ALTER COLUMN
+    updated_on TYPE TIMESTAMPTZ USING updated_on AT TIME ZONE 'UTC',
+ALTER COLUMN
+    deleted_at TYPE TIMESTAMPTZ USING deleted_at AT TIME ZONE 'UTC';
+
+ALTER TABLE
+    answers
+ALTER COLUMN
+    created_on TYPE TIMESTAMPTZ USING created_on AT TIME ZONE 'UTC';
+
+ALTER TABLE
+    accounts
+ALTER COLUMN
+    created_on TYPE TIMESTAMPTZ USING created_on AT TIME ZONE 'UTC';
+
+ALTER TABLE
+    reports
+ALTER COLUMN
+    created_on TYPE TIMESTAMPTZ USING created_on AT TIME ZONE 'UTC',
+ALTER COLUMN
+    resolved_on TYPE TIMESTAMPTZ USING resolved_on AT TIME ZONE 'UTC';
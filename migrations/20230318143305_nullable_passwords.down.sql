@@ -0,0 +1,13 @@
+UPDATE
+    accounts
+SET
+    password = ''
+WHERE
+    password IS NULL;
+
+ALTER TABLE
+    accounts
+ALTER COLUMN
+    password
+SET
+    NOT NULL;
\ No newline at end of file
@@ -0,0 +1,13 @@
+-- Accounts imported without their password hash, and the placeholders
+-- of the account keys migration, have none instead of an empty one
+ALTER TABLE
+    accounts
+ALTER COLUMN
+    password DROP NOT NULL;
+
+UPDATE
+    accounts
+SET
+    password = NULL
+WHERE
+    password = '';
\ No newline at end of file
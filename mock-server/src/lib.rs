@@ -23,6 +23,13 @@ impl MockServer {
 
     async fn check_profanity(_: (), content: Bytes) -> Result<impl warp::Reply, warp::Rejection> {
         let content = String::from_utf8(content.to_vec()).expect("Invalid UTF-8");
+        if content.contains("blocked by proxy") {
+            // Error page of a proxy in front of the API, not JSON
+            return Ok(warp::reply::with_status(
+                warp::reply::html("<html>Forbidden</html>").into_response(),
+                http::StatusCode::FORBIDDEN,
+            ));
+        }
         if content.contains("shitty") {
             Ok(warp::reply::with_status(
                 warp::reply::json(&json!({
@@ -40,7 +47,8 @@ impl MockServer {
                     "bad_words_total": 1,
                     "censored_content": "this is a ****** sentence",
                     "content": "this is a shitty sentence"
-                })),
+                }))
+                .into_response(),
                 http::StatusCode::OK,
             ))
         } else {
@@ -50,7 +58,8 @@ impl MockServer {
                     "bad_words_total": 0,
                     "censored_content": "",
                     "content": "this is a sentence"
-                })),
+                }))
+                .into_response(),
                 http::StatusCode::OK,
             ))
         }
@@ -68,7 +77,7 @@ impl MockServer {
 
     pub fn oneshot(&self) -> OneshotHandler {
         let (tx, rx) = oneshot::channel::<i32>();
-        let routes = Self::build_routes(&self);
+        let routes = Self::build_routes(self);
 
         let (_, server) = warp::serve(routes).bind_with_graceful_shutdown(self.socket, async {
             rx.await.ok();
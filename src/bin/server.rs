@@ -1,16 +1,50 @@
-use warp_rest_api::config;
-use warp_rest_api::{run, setup_store};
+use warp_rest_api::config::{self, Command};
+use warp_rest_api::{
+    export_records, import_records, load_fixture, migrate, run, setup_moderation, setup_store,
+};
 
 #[tokio::main]
 async fn main() -> Result<(), handle_errors::Error> {
     dotenv::dotenv().ok();
 
     let config = config::Config::new().expect("Config can't be set");
-    let store = setup_store(&config).await?;
 
-    tracing::info!("warp-rest-api build ID {}", env!("WARP_REST_API_VERSION"));
+    match config.command() {
+        Command::Serve => {
+            let store = setup_store(&config).await?;
+            let moderation = setup_moderation(&config)?;
 
-    run(config, store).await;
+            tracing::info!("warp-rest-api build ID {}", env!("WARP_REST_API_VERSION"));
+
+            run(config, store, moderation).await;
+        }
+        Command::Migrate(command) => migrate(&config, &command).await?,
+        Command::Seed { file } => {
+            let store = setup_store(&config).await?;
+            let seeded = load_fixture(&store, &file).await?;
+            println!(
+                "Added {} accounts, {} questions and {} answers",
+                seeded.accounts, seeded.questions, seeded.answers
+            );
+        }
+        Command::Export {
+            with_passwords,
+            output,
+        } => {
+            let store = setup_store(&config).await?;
+            let written = export_records(&store, with_passwords, output.as_deref()).await?;
+            // The records themselves may go to stdout
+            eprintln!("Exported {written} records");
+        }
+        Command::Import { file } => {
+            let store = setup_store(&config).await?;
+            let imported = import_records(&store, &file).await?;
+            println!(
+                "Imported {} accounts, {} questions and {} answers",
+                imported.accounts, imported.questions, imported.answers
+            );
+        }
+    }
 
     Ok(())
 }
@@ -2,32 +2,66 @@
 #![warn(clippy::pedantic)]
 #![warn(clippy::cargo)]
 #![allow(clippy::module_name_repetitions)]
+// Duplicates deep in the dependency tree, which only a crate can allow
+#![allow(clippy::multiple_crate_versions)]
 #![allow(opaque_hidden_inferred_bound)]
 
 pub use handle_errors;
 
-use crate::routes::{answer, authentication, question};
-use tokio::sync::{oneshot, oneshot::Sender};
+use crate::{
+    profanity::{
+        ApiLayerFilter, CircuitBreaker, ContentFilter, Moderation, NoopFilter, VerdictCache,
+        WordlistFilter,
+    },
+    routes::{answer, authentication, moderation, profile, question, report},
+    types::report::ReportThreshold,
+};
+use futures::TryStreamExt;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions, PgSslMode};
+use std::{str::FromStr, sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt},
+    sync::{oneshot, oneshot::Sender},
+};
+use tracing::{event, Level};
 use tracing_subscriber::fmt::format::FmtSpan;
 use warp::{http::Method, Filter, Reply};
 
 pub mod config;
-mod profanity;
+mod migrations;
+pub mod profanity;
+mod reputation;
 mod routes;
+pub mod seed;
 mod store;
+pub mod transfer;
 pub mod types;
 
 pub struct OneshotHandler {
     pub sender: Sender<i32>,
 }
 
-async fn build_routes(store: store::Store) -> impl Filter<Extract = impl Reply> + Clone {
+#[allow(clippy::too_many_lines)]
+fn build_routes(
+    store: store::Store,
+    moderation: Moderation,
+) -> impl Filter<Extract = impl Reply> + Clone {
     let store_filter = warp::any().map(move || store.clone());
+    let moderation_filter = warp::any().map(move || moderation.clone());
 
     let cors = warp::cors()
         .allow_any_origin()
         .allow_header("content-type")
-        .allow_methods(&[Method::PUT, Method::DELETE, Method::GET, Method::POST]);
+        .allow_header("if-match")
+        .allow_header("if-none-match")
+        .expose_header("etag")
+        .allow_methods(&[
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::GET,
+            Method::POST,
+        ]);
 
     let get_questions = warp::get()
         .and(warp::path("questions"))
@@ -40,6 +74,8 @@ async fn build_routes(store: store::Store) -> impl Filter<Extract = impl Reply>
         .and(warp::path("questions"))
         .and(warp::path::param::<i32>())
         .and(warp::path::end())
+        .and(warp::header::optional::<String>("if-none-match"))
+        .and(authentication::optional_auth())
         .and(store_filter.clone())
         .and_then(question::get_question_by_id);
 
@@ -48,6 +84,7 @@ async fn build_routes(store: store::Store) -> impl Filter<Extract = impl Reply>
         .and(warp::path::end())
         .and(authentication::auth())
         .and(store_filter.clone())
+        .and(moderation_filter.clone())
         .and(warp::body::json())
         .and_then(question::add_question);
 
@@ -55,27 +92,188 @@ async fn build_routes(store: store::Store) -> impl Filter<Extract = impl Reply>
         .and(warp::path("questions"))
         .and(warp::path::param::<i32>())
         .and(warp::path::end())
+        .and(warp::header::optional::<String>("if-match"))
         .and(authentication::auth())
         .and(store_filter.clone())
+        .and(moderation_filter.clone())
         .and(warp::body::json())
         .and_then(question::update_question);
 
+    let patch_question = warp::patch()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(warp::header::optional::<String>("if-match"))
+        .and(authentication::auth())
+        .and(store_filter.clone())
+        .and(moderation_filter.clone())
+        .and(warp::body::json())
+        .and_then(question::patch_question);
+
     let delete_question = warp::delete()
         .and(warp::path("questions"))
         .and(warp::path::param::<i32>())
         .and(warp::path::end())
+        .and(warp::header::optional::<String>("if-match"))
         .and(authentication::auth())
         .and(store_filter.clone())
         .and_then(question::delete_question);
 
+    let restore_question = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("restore"))
+        .and(warp::path::end())
+        .and(authentication::auth())
+        .and(store_filter.clone())
+        .and_then(question::restore_question);
+
     let add_answer = warp::post()
         .and(warp::path("answers"))
         .and(warp::path::end())
         .and(authentication::auth())
         .and(store_filter.clone())
+        .and(moderation_filter.clone())
         .and(warp::body::json())
         .and_then(answer::add_answer);
 
+    let get_profile = warp::get()
+        .and(warp::path("users"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(profile::get_profile);
+
+    let get_reputation = warp::get()
+        .and(warp::path("users"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("reputation"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(profile::get_reputation);
+
+    let update_profile = warp::put()
+        .and(warp::path("account"))
+        .and(warp::path("profile"))
+        .and(warp::path::end())
+        .and(authentication::auth())
+        .and(store_filter.clone())
+        .and(moderation_filter.clone())
+        .and(warp::body::json())
+        .and_then(profile::update_profile);
+
+    let get_own_posts = warp::get()
+        .and(warp::path("account"))
+        .and(warp::path("moderation"))
+        .and(warp::path::end())
+        .and(authentication::auth())
+        .and(store_filter.clone())
+        .and_then(moderation::get_own_posts);
+
+    let get_moderation_queue = warp::get()
+        .and(warp::path("moderation"))
+        .and(warp::path("queue"))
+        .and(warp::path::end())
+        .and(authentication::auth())
+        .and(store_filter.clone())
+        .and_then(moderation::get_queue);
+
+    let approve_question = warp::post()
+        .and(warp::path("moderation"))
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("approve"))
+        .and(warp::path::end())
+        .and(authentication::auth())
+        .and(store_filter.clone())
+        .and_then(moderation::approve_question);
+
+    let reject_question = warp::post()
+        .and(warp::path("moderation"))
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("reject"))
+        .and(warp::path::end())
+        .and(authentication::auth())
+        .and(store_filter.clone())
+        .and_then(moderation::reject_question);
+
+    let approve_answer = warp::post()
+        .and(warp::path("moderation"))
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("approve"))
+        .and(warp::path::end())
+        .and(authentication::auth())
+        .and(store_filter.clone())
+        .and_then(moderation::approve_answer);
+
+    let reject_answer = warp::post()
+        .and(warp::path("moderation"))
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("reject"))
+        .and(warp::path::end())
+        .and(authentication::auth())
+        .and(store_filter.clone())
+        .and_then(moderation::reject_answer);
+
+    let report_question = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("report"))
+        .and(warp::path::end())
+        .and(authentication::auth())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(report::report_question);
+
+    let report_answer = warp::post()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("report"))
+        .and(warp::path::end())
+        .and(authentication::auth())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(report::report_answer);
+
+    let get_reports = warp::get()
+        .and(warp::path("moderation"))
+        .and(warp::path("reports"))
+        .and(warp::path::end())
+        .and(authentication::auth())
+        .and(store_filter.clone())
+        .and_then(moderation::get_reports);
+
+    let resolve_report = warp::post()
+        .and(warp::path("moderation"))
+        .and(warp::path("reports"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("resolve"))
+        .and(warp::path::end())
+        .and(authentication::auth())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(moderation::resolve_report);
+
+    let export = warp::get()
+        .and(warp::path("admin"))
+        .and(warp::path("export"))
+        .and(warp::path::end())
+        .and(authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::transfer::export);
+
+    let import = warp::post()
+        .and(warp::path("admin"))
+        .and(warp::path("import"))
+        .and(warp::path::end())
+        .and(authentication::auth())
+        .and(store_filter.clone())
+        .and(warp::body::stream())
+        .and_then(routes::transfer::import);
+
     let registration = warp::post()
         .and(warp::path("registration"))
         .and(warp::path::end())
@@ -90,61 +288,469 @@ async fn build_routes(store: store::Store) -> impl Filter<Extract = impl Reply>
         .and(warp::body::json())
         .and_then(authentication::login);
 
-    add_question
+    // Boxing the groups keeps the nested filter futures
+    // from overflowing the stack of debug builds
+    let question_routes = add_question
         .or(get_questions)
         .or(get_one_question)
         .or(update_question)
+        .or(patch_question)
         .or(delete_question)
-        .or(add_answer)
+        .or(restore_question)
+        .or(report_question)
+        .boxed();
+
+    let answer_routes = add_answer.or(report_answer).boxed();
+
+    let account_routes = get_profile
+        .or(get_reputation)
+        .or(update_profile)
+        .or(get_own_posts)
         .or(registration)
         .or(login)
+        .boxed();
+
+    let moderation_routes = get_moderation_queue
+        .or(approve_question)
+        .or(reject_question)
+        .or(approve_answer)
+        .or(reject_answer)
+        .or(get_reports)
+        .or(resolve_report)
+        .boxed();
+
+    let admin_routes = export.or(import).boxed();
+
+    question_routes
+        .or(answer_routes)
+        .or(account_routes)
+        .or(moderation_routes)
+        .or(admin_routes)
         .with(cors)
         .with(warp::trace::request())
         .recover(handle_errors::return_error)
 }
 
+/// Connects to the database and applies pending migrations, unless
+/// `--no-migrate` is set
+///
+/// # Errors
+///
+/// If the database cannot be reached or a migration fails
 pub async fn setup_store(config: &config::Config) -> Result<store::Store, handle_errors::Error> {
-    let store = store::Store::new(&format!(
-        "postgres://{}:{}@{}:{}/{}",
-        config.db_user, config.db_password, config.db_host, config.db_port, config.db_name
-    ))
-    .await
-    .map_err(handle_errors::Error::DatabaseQueryError)?;
-
-    sqlx::migrate!()
-        .run(&store.clone().connection)
+    setup_tracing(config);
+
+    let mut store = store::PgStore::new(db_connect_options(config)?, db_pool_options(config))
+        .await
+        .map_err(handle_errors::Error::from)?
+        .with_report_threshold(ReportThreshold(config.report_threshold));
+
+    event!(
+        target: "warp-rest-api",
+        Level::INFO,
+        database = %config.redacted_db_url(),
+        "Connected to database"
+    );
+
+    if let Some(read_replica_url) = &config.read_replica_url {
+        let connect_options =
+            url_connect_options(read_replica_url, pg_ssl_mode(config.db_ssl_mode))?;
+        // Connects on the first read, so a replica which is down only
+        // sends the reads to the primary instead of stopping the server
+        store = store.with_read_replica(db_pool_options(config).connect_lazy_with(connect_options));
+
+        event!(
+            target: "warp-rest-api",
+            Level::INFO,
+            replica = %config.redacted_replica_url().unwrap_or_default(),
+            "Reading from replica"
+        );
+    }
+
+    if config.no_migrate {
+        let pending = migrations::status(&store.connection)
+            .await?
+            .into_iter()
+            .filter(|migration| migration.state != migrations::MigrationState::Applied)
+            .count();
+        if pending > 0 {
+            event!(
+                target: "warp-rest-api",
+                Level::WARN,
+                pending,
+                "Database schema is not up to date, run `migrate up`"
+            );
+        }
+    } else {
+        migrations::up(&store.connection).await?;
+    }
+
+    Ok(store::Store::new(Arc::new(store)))
+}
+
+/// Runs a `migrate` subcommand and prints what it did
+///
+/// # Errors
+///
+/// If the database cannot be reached or a migration fails
+pub async fn migrate(
+    config: &config::Config,
+    command: &config::MigrateCommand,
+) -> Result<(), handle_errors::Error> {
+    setup_tracing(config);
+
+    let pool = db_pool_options(config)
+        .connect_with(db_connect_options(config)?)
+        .await
+        .map_err(handle_errors::Error::from)?;
+
+    match command {
+        config::MigrateCommand::Up => {
+            let pending: Vec<_> = migrations::status(&pool)
+                .await?
+                .into_iter()
+                .filter(|migration| migration.state == migrations::MigrationState::Pending)
+                .collect();
+            migrations::up(&pool).await?;
+
+            for migration in &pending {
+                println!("Applied {} {}", migration.version, migration.description);
+            }
+            if pending.is_empty() {
+                println!("No pending migrations");
+            }
+        }
+        config::MigrateCommand::Down { steps } => {
+            let reverted = migrations::down(&pool, *steps).await?;
+
+            for version in &reverted {
+                println!("Reverted {version}");
+            }
+            if reverted.is_empty() {
+                println!("No migrations reverted");
+            }
+        }
+        config::MigrateCommand::Status => {
+            for migration in migrations::status(&pool).await? {
+                let state = match migration.state {
+                    migrations::MigrationState::Applied => "applied",
+                    migrations::MigrationState::Pending => "pending",
+                    migrations::MigrationState::Changed => "changed",
+                };
+                println!(
+                    "{} {:<7} {}",
+                    migration.version, state, migration.description
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Loads a fixture file into the store and logs what was added
+///
+/// # Errors
+///
+/// If the fixture cannot be read, refers to a missing account or
+/// cannot be stored
+pub async fn load_fixture(
+    store: &store::Store,
+    path: &std::path::Path,
+) -> Result<seed::Seeded, handle_errors::Error> {
+    let seeded = seed::seed(store, seed::Fixture::from_file(path)?).await?;
+    event!(
+        target: "warp-rest-api",
+        Level::INFO,
+        accounts = seeded.accounts,
+        questions = seeded.questions,
+        answers = seeded.answers,
+        "Loaded fixture"
+    );
+
+    Ok(seeded)
+}
+
+/// Writes every record as NDJSON to `output`, or to stdout without one,
+/// and returns how many lines were written
+///
+/// # Errors
+///
+/// If the store cannot be read or the output cannot be written
+pub async fn export_records(
+    store: &store::Store,
+    with_passwords: bool,
+    output: Option<&std::path::Path>,
+) -> Result<usize, handle_errors::Error> {
+    let writer: Box<dyn tokio::io::AsyncWrite + Send + Unpin> = match output {
+        Some(path) => Box::new(
+            tokio::fs::File::create(path)
+                .await
+                .map_err(handle_errors::Error::TransferError)?,
+        ),
+        None => Box::new(tokio::io::stdout()),
+    };
+    let mut writer = tokio::io::BufWriter::new(writer);
+
+    let mut lines = transfer::export_lines(store, with_passwords);
+    let mut written = 0;
+    while let Some(line) = lines.try_next().await? {
+        writer
+            .write_all(line.as_bytes())
+            .await
+            .map_err(handle_errors::Error::TransferError)?;
+        written += 1;
+    }
+    writer
+        .flush()
+        .await
+        .map_err(handle_errors::Error::TransferError)?;
+
+    event!(target: "warp-rest-api", Level::INFO, records = written, "Exported records");
+    Ok(written)
+}
+
+/// Imports an NDJSON export, reading the file line by line
+///
+/// # Errors
+///
+/// If the file cannot be read, a line is no record or a record
+/// cannot be stored, see `transfer::import`
+pub async fn import_records(
+    store: &store::Store,
+    path: &std::path::Path,
+) -> Result<types::export::Imported, handle_errors::Error> {
+    let file = tokio::fs::File::open(path)
         .await
-        .map_err(handle_errors::Error::MigrationError)?;
+        .map_err(handle_errors::Error::TransferError)?;
+    let lines = futures::stream::unfold(
+        tokio::io::BufReader::new(file).lines(),
+        |mut lines| async move {
+            lines
+                .next_line()
+                .await
+                .transpose()
+                .map(|line| (line, lines))
+        },
+    );
+
+    let imported = transfer::import(store, Box::pin(lines), true).await?;
+    event!(
+        target: "warp-rest-api",
+        Level::INFO,
+        accounts = imported.accounts,
+        questions = imported.questions,
+        answers = imported.answers,
+        "Imported records"
+    );
+
+    Ok(imported)
+}
 
+fn setup_tracing(config: &config::Config) {
     let log_filter = format!(
         "handle_errors={},warp-rest-api={},warp={}",
         config.log_level, config.log_level, config.log_level
     );
 
-    tracing_subscriber::fmt()
+    let subscriber = tracing_subscriber::fmt()
         // Use the filter we built above to determine which traces to record.
         .with_env_filter(log_filter)
         // Record an event when each span closes. This can be used to time our
         // routes' durations!
-        .with_span_events(FmtSpan::CLOSE)
-        .init();
+        .with_span_events(FmtSpan::CLOSE);
+
+    // Other commands keep stdout to what they print, e.g. an export
+    if config.command() == config::Command::Serve {
+        subscriber.init();
+    } else {
+        subscriber.with_writer(std::io::stderr).init();
+    }
+}
+
+/// Built from the single settings unless a `DATABASE_URL` is given,
+/// so credentials never have to be URL-encoded
+fn db_connect_options(config: &config::Config) -> Result<PgConnectOptions, handle_errors::Error> {
+    let ssl_mode = pg_ssl_mode(config.db_ssl_mode);
+
+    if let Some(database_url) = &config.database_url {
+        return url_connect_options(database_url, ssl_mode);
+    }
+
+    Ok(PgConnectOptions::new()
+        .host(&config.db_host)
+        .port(config.db_port)
+        .username(&config.db_user)
+        .password(&config.db_password)
+        .database(&config.db_name)
+        .ssl_mode(ssl_mode))
+}
+
+fn pg_ssl_mode(ssl_mode: config::SslMode) -> PgSslMode {
+    match ssl_mode {
+        config::SslMode::Disable => PgSslMode::Disable,
+        config::SslMode::Allow => PgSslMode::Allow,
+        config::SslMode::Prefer => PgSslMode::Prefer,
+        config::SslMode::Require => PgSslMode::Require,
+        config::SslMode::VerifyCa => PgSslMode::VerifyCa,
+        config::SslMode::VerifyFull => PgSslMode::VerifyFull,
+    }
+}
+
+/// The `sslmode` of the connection string wins over `ssl_mode`
+fn url_connect_options(
+    connection_string: &str,
+    ssl_mode: PgSslMode,
+) -> Result<PgConnectOptions, handle_errors::Error> {
+    let options = PgConnectOptions::from_str(connection_string)
+        .map_err(handle_errors::Error::DatabaseQueryError)?;
+    let has_ssl_mode = url::Url::parse(connection_string).is_ok_and(|url| {
+        url.query_pairs()
+            .any(|(key, _)| key == "sslmode" || key == "ssl-mode")
+    });
 
-    Ok(store)
+    Ok(if has_ssl_mode {
+        options
+    } else {
+        options.ssl_mode(ssl_mode)
+    })
+}
+
+fn db_pool_options(config: &config::Config) -> PgPoolOptions {
+    let idle_timeout =
+        (config.db_idle_timeout > 0).then(|| Duration::from_secs(config.db_idle_timeout));
+
+    PgPoolOptions::new()
+        .max_connections(config.db_max_connections)
+        .min_connections(config.db_min_connections)
+        .connect_timeout(Duration::from_secs(config.db_acquire_timeout))
+        .idle_timeout(idle_timeout)
+}
+
+/// Builds the content filter chosen by `--content-filter`
+///
+/// # Errors
+///
+/// If the HTTP client of the API cannot be built or a wordlist
+/// cannot be read
+///
+/// # Panics
+///
+/// If the API is chosen but `BAD_WORDS_API_KEY` or `API_LAYER_URL`
+/// is not set
+pub fn setup_moderation(config: &config::Config) -> Result<Moderation, handle_errors::Error> {
+    let content_filter: Arc<dyn ContentFilter> = match config.content_filter {
+        config::ContentFilterKind::ApiLayer => {
+            let api_layer = ApiLayerFilter::new(
+                std::env::var("BAD_WORDS_API_KEY").expect("BAD WORDS API KEY NOT SET"),
+                std::env::var("API_LAYER_URL").expect("APILAYER URL NOT SET"),
+                Duration::from_millis(config.moderation_connect_timeout),
+                Duration::from_millis(config.moderation_timeout),
+            )?;
+            let fallback: Option<Arc<dyn ContentFilter>> = if config.moderation_fallback {
+                Some(Arc::new(load_wordlists(config)?))
+            } else {
+                None
+            };
+            let breaker = Arc::new(CircuitBreaker::new(
+                Arc::new(api_layer),
+                fallback,
+                config.circuit_breaker_threshold,
+                Duration::from_secs(config.circuit_breaker_cooldown),
+            ));
+
+            // Cached verdicts are still served while the circuit is open
+            if config.moderation_cache_size > 0 {
+                let cache = Arc::new(VerdictCache::new(
+                    breaker,
+                    Duration::from_secs(config.moderation_cache_ttl),
+                    config.moderation_cache_size,
+                ));
+                if config.moderation_cache_stats_interval > 0 {
+                    spawn_cache_stats(
+                        cache.clone(),
+                        Duration::from_secs(config.moderation_cache_stats_interval),
+                    );
+                }
+                cache
+            } else {
+                breaker
+            }
+        }
+        config::ContentFilterKind::Wordlist => Arc::new(load_wordlists(config)?),
+        config::ContentFilterKind::None => Arc::new(NoopFilter),
+    };
+
+    Ok(Moderation::new(content_filter, config.moderation_policy))
+}
+
+fn load_wordlists(config: &config::Config) -> Result<WordlistFilter, handle_errors::Error> {
+    let filter = WordlistFilter::from_dir(
+        std::path::Path::new(&config.wordlist_dir),
+        &config.wordlist_languages,
+    )?;
+    event!(
+        target: "warp-rest-api",
+        Level::INFO,
+        languages = ?filter.languages(),
+        "Loaded wordlists"
+    );
+
+    Ok(filter)
+}
+
+/// Periodically logs how well the verdict cache spares the moderation API
+fn spawn_cache_stats(cache: Arc<VerdictCache>, period: Duration) {
+    let mut interval = tokio::time::interval(period);
+
+    tokio::task::spawn(async move {
+        loop {
+            interval.tick().await;
+            let stats = cache.stats();
+            event!(
+                target: "warp-rest-api",
+                Level::INFO,
+                hits = stats.hits,
+                misses = stats.misses,
+                entries = stats.entries,
+                "Verdict cache stats"
+            );
+        }
+    });
+}
+
+/// Periodically purges soft-deleted questions once they are older
+/// than the configured retention period.
+fn spawn_question_purge(config: &config::Config, store: store::Store) {
+    let retention_days = config.question_retention_days;
+    let mut interval = tokio::time::interval(Duration::from_secs(config.purge_interval));
+
+    tokio::task::spawn(async move {
+        loop {
+            interval.tick().await;
+            match store.purge_deleted_questions(retention_days).await {
+                Ok(purged) => {
+                    event!(target: "warp-rest-api", Level::INFO, purged, "PURGE deleted Questions");
+                }
+                Err(e) => event!(target: "warp-rest-api", Level::ERROR, "{}", e),
+            }
+        }
+    });
 }
 
-pub async fn run(config: config::Config, store: store::Store) {
-    let routes = build_routes(store).await;
+pub async fn run(config: config::Config, store: store::Store, moderation: Moderation) {
+    spawn_question_purge(&config, store.clone());
+    let routes = build_routes(store, moderation);
     warp::serve(routes).run(([0, 0, 0, 0], config.port)).await;
 }
 
-pub async fn oneshot(store: store::Store) -> OneshotHandler {
-    let routes = build_routes(store).await;
+// Stays async for the integration tests, which await it
+#[allow(clippy::unused_async)]
+pub async fn oneshot(store: store::Store, moderation: Moderation) -> OneshotHandler {
+    let routes = build_routes(store, moderation);
     let (tx, rx) = oneshot::channel::<i32>();
 
-    let socket: std::net::SocketAddr = "127.0.0.1:3030"
-        .to_string()
-        .parse()
-        .expect("Not a valid address");
+    let socket = std::net::SocketAddr::from(([127, 0, 0, 1], 3030));
 
     let (_, server) = warp::serve(routes).bind_with_graceful_shutdown(socket, async {
         rx.await.ok();
@@ -0,0 +1,89 @@
+use sqlx::{
+    migrate::{Migrate, MigrateError, Migrator},
+    postgres::PgPool,
+};
+use std::collections::HashMap;
+
+use handle_errors::Error;
+
+/// The migrations in `migrations/`, embedded at compile time
+static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// Where a migration of this build stands in the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but from a different `.up.sql` than this build has
+    Changed,
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+}
+
+/// Applies all pending migrations
+pub async fn up(pool: &PgPool) -> Result<(), Error> {
+    MIGRATOR.run(pool).await.map_err(Error::MigrationError)
+}
+
+/// Reverts the latest `steps` applied migrations with their `.down.sql`,
+/// newest first. Returns the versions which were reverted.
+pub async fn down(pool: &PgPool, steps: usize) -> Result<Vec<i64>, Error> {
+    let mut applied: Vec<i64> = applied_checksums(pool).await?.into_keys().collect();
+    applied.sort_unstable();
+
+    let keep = applied.len().saturating_sub(steps);
+    // `undo` reverts everything newer than the target, 0 is older than all
+    let target = keep
+        .checked_sub(1)
+        .map_or(0, |last_kept| applied[last_kept]);
+    MIGRATOR
+        .undo(pool, target)
+        .await
+        .map_err(Error::MigrationError)?;
+
+    let mut reverted = applied.split_off(keep);
+    reverted.reverse();
+    Ok(reverted)
+}
+
+/// Every migration of this build, oldest first
+pub async fn status(pool: &PgPool) -> Result<Vec<MigrationStatus>, Error> {
+    let applied = applied_checksums(pool).await?;
+
+    Ok(MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| MigrationStatus {
+            version: migration.version,
+            description: migration.description.to_string(),
+            state: match applied.get(&migration.version) {
+                Some(checksum) if *checksum == *migration.checksum => MigrationState::Applied,
+                Some(_) => MigrationState::Changed,
+                None => MigrationState::Pending,
+            },
+        })
+        .collect())
+}
+
+async fn applied_checksums(pool: &PgPool) -> Result<HashMap<i64, Vec<u8>>, Error> {
+    let listed = async {
+        let mut connection = pool.acquire().await?;
+        connection.ensure_migrations_table().await?;
+        connection.list_applied_migrations().await
+    }
+    .await;
+
+    match listed {
+        Ok(applied) => Ok(applied
+            .into_iter()
+            .map(|migration| (migration.version, migration.checksum.into_owned()))
+            .collect()),
+        Err(MigrateError::Execute(e)) => Err(Error::from(e)),
+        Err(e) => Err(Error::MigrationError(e)),
+    }
+}
@@ -0,0 +1,208 @@
+use async_trait::async_trait;
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::{ContentFilter, Verdict};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct APIResponse {
+    message: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct BadWord {
+    original: String,
+    word: String,
+    deviations: i64,
+    info: i64,
+    #[serde(rename = "replacedLen")]
+    replaced_len: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct BadWordsResponse {
+    content: String,
+    bad_words_total: i64,
+    bad_words_list: Vec<BadWord>,
+    censored_content: String,
+}
+
+/// Censors content through the `APILayer` bad words API.
+/// All requests share one client and with it its connection pool.
+#[derive(Clone)]
+pub struct ApiLayerFilter {
+    api_key: String,
+    url: String,
+    client: ClientWithMiddleware,
+}
+
+impl ApiLayerFilter {
+    /// # Errors
+    ///
+    /// If the HTTP client cannot be built
+    pub fn new(
+        api_key: String,
+        url: String,
+        connect_timeout: Duration,
+        request_timeout: Duration,
+    ) -> Result<Self, handle_errors::Error> {
+        let http_client = reqwest::Client::builder()
+            .connect_timeout(connect_timeout)
+            .timeout(request_timeout)
+            .build()
+            .map_err(handle_errors::Error::ReqwestAPIError)?;
+
+        // Writes wait for the check, so retries back off only briefly.
+        // Longer outages are handled by the circuit breaker.
+        let retry_policy = ExponentialBackoff::builder()
+            .retry_bounds(Duration::from_millis(100), Duration::from_secs(1))
+            .build_with_max_retries(2);
+        let client = ClientBuilder::new(http_client)
+            // Retry failed requests.
+            .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+            .build();
+
+        Ok(ApiLayerFilter {
+            api_key,
+            url,
+            client,
+        })
+    }
+}
+
+// Handlers log their arguments, so the API key must never be printed
+impl std::fmt::Debug for ApiLayerFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ApiLayerFilter")
+            .field("url", &self.url)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl ContentFilter for ApiLayerFilter {
+    async fn check(&self, content: String) -> Result<Verdict, handle_errors::Error> {
+        check_profanity(self, content).await
+    }
+}
+
+async fn check_profanity(
+    filter: &ApiLayerFilter,
+    content: String,
+) -> Result<Verdict, handle_errors::Error> {
+    let res = filter
+        .client
+        .post(format!("{}/bad_words?censor_character=*", filter.url))
+        .header("apikey", &filter.api_key)
+        .body(content.clone())
+        .send()
+        .await
+        .map_err(handle_errors::Error::MiddlewareReqwestAPIError)?;
+
+    if !res.status().is_success() {
+        if res.status().is_client_error() {
+            let err = transform_error(res).await;
+            return Err(handle_errors::Error::ClientError(err));
+        }
+        let err = transform_error(res).await;
+        return Err(handle_errors::Error::ServerError(err));
+    }
+
+    match res.json::<BadWordsResponse>().await {
+        // `censored_content` is empty if there was nothing to censor
+        Ok(res) if res.bad_words_total <= 0 => Ok(Verdict::clean(content)),
+        Ok(res) => Ok(Verdict {
+            censored: res.censored_content,
+            bad_words: usize::try_from(res.bad_words_total).unwrap_or(usize::MAX),
+            fallback: false,
+        }),
+        Err(e) => Err(handle_errors::Error::ReqwestAPIError(e)),
+    }
+}
+
+/// Longest part of a non-JSON error body which ends up in the error
+const MAX_ERROR_BODY: usize = 200;
+
+/// Error bodies are not always JSON, e.g. error pages of a proxy
+/// in front of the API, so the raw body is kept as the message then.
+async fn transform_error(res: reqwest::Response) -> handle_errors::APILayerError {
+    let status = res.status();
+    let body = res.text().await.unwrap_or_default();
+
+    let message = match serde_json::from_str::<APIResponse>(&body) {
+        Ok(response) => response.message,
+        Err(_) if body.trim().is_empty() => status
+            .canonical_reason()
+            .unwrap_or("Unknown error")
+            .to_string(),
+        Err(_) => body.trim().chars().take(MAX_ERROR_BODY).collect(),
+    };
+
+    handle_errors::APILayerError {
+        status: status.as_u16(),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod api_layer_tests {
+    use super::{ApiLayerFilter, ContentFilter, Verdict};
+    use std::time::Duration;
+
+    use mock_server::{MockServer, OneshotHandler};
+
+    #[tokio::test]
+    async fn run() {
+        let handler = run_mock();
+        censor_profane_words().await;
+        no_profane_words().await;
+        non_json_error_body().await;
+        let _ = handler.sender.send(1);
+    }
+
+    fn run_mock() -> OneshotHandler {
+        let socket = "127.0.0.1:3030"
+            .to_string()
+            .parse()
+            .expect("Not a valid address");
+        let mock = MockServer::new(socket);
+
+        mock.oneshot()
+    }
+
+    fn filter() -> ApiLayerFilter {
+        ApiLayerFilter::new(
+            "YES".to_string(),
+            "http://127.0.0.1:3030".to_string(),
+            Duration::from_secs(1),
+            Duration::from_secs(5),
+        )
+        .unwrap()
+    }
+
+    async fn censor_profane_words() {
+        let content = "This is a shitty sentence".to_string();
+        let verdict = filter().check(content).await.unwrap();
+        assert_eq!(verdict.censored, "this is a ****** sentence");
+        assert_eq!(verdict.bad_words, 1);
+    }
+
+    async fn no_profane_words() {
+        let content = "This is a sentence".to_string();
+        let verdict = filter().check(content.clone()).await.unwrap();
+        assert_eq!(verdict, Verdict::clean(content));
+    }
+
+    async fn non_json_error_body() {
+        let content = "blocked by proxy".to_string();
+        match filter().check(content).await {
+            Err(handle_errors::Error::ClientError(err)) => {
+                assert_eq!(err.status, 403);
+                assert_eq!(err.message, "<html>Forbidden</html>");
+            }
+            other => panic!("expected a client error, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tracing::{event, Level};
+
+use super::{ContentFilter, Verdict};
+
+type ContentHash = [u8; 32];
+
+#[derive(Debug)]
+struct Entry {
+    verdict: Verdict,
+    expires: Instant,
+    /// Position in `Entries::recent`
+    used: u64,
+}
+
+/// The verdicts together with the order they were last used in, so
+/// the least recently used one is found without a scan
+#[derive(Debug, Default)]
+struct Entries {
+    verdicts: HashMap<ContentHash, Entry>,
+    recent: BTreeMap<u64, ContentHash>,
+    uses: u64,
+}
+
+impl Entries {
+    fn touch(&mut self, key: ContentHash) -> u64 {
+        self.uses += 1;
+        self.recent.insert(self.uses, key);
+        self.uses
+    }
+
+    fn remove(&mut self, key: &ContentHash) {
+        if let Some(entry) = self.verdicts.remove(key) {
+            self.recent.remove(&entry.used);
+        }
+    }
+}
+
+/// How often the cache could answer a check by itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+/// Remembers the verdicts of a filter, so identical content is only
+/// checked once per `ttl`. Entries are keyed by the SHA-256 of the
+/// content. Errors and verdicts of a fallback filter are never cached.
+/// When full, the least recently used verdict makes room.
+#[derive(Debug)]
+pub struct VerdictCache {
+    filter: Arc<dyn ContentFilter>,
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<Entries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl VerdictCache {
+    /// Keeps at most `capacity` verdicts for `ttl` each
+    pub fn new(filter: Arc<dyn ContentFilter>, ttl: Duration, capacity: usize) -> Self {
+        VerdictCache {
+            filter,
+            ttl,
+            capacity: capacity.max(1),
+            entries: Mutex::new(Entries::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// # Panics
+    ///
+    /// If a thread panicked while holding the cache
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self
+                .entries
+                .lock()
+                .expect("verdict cache lock poisoned")
+                .verdicts
+                .len(),
+        }
+    }
+
+    fn get(&self, key: &ContentHash) -> Option<Verdict> {
+        let mut entries = self.entries.lock().expect("verdict cache lock poisoned");
+
+        let used = match entries.verdicts.get(key) {
+            Some(entry) if entry.expires > Instant::now() => entry.used,
+            Some(_) => {
+                entries.remove(key);
+                return None;
+            }
+            None => return None,
+        };
+
+        entries.recent.remove(&used);
+        let used = entries.touch(*key);
+        let entry = entries.verdicts.get_mut(key)?;
+        entry.used = used;
+        Some(entry.verdict.clone())
+    }
+
+    fn insert(&self, key: ContentHash, verdict: Verdict) {
+        let mut entries = self.entries.lock().expect("verdict cache lock poisoned");
+
+        entries.remove(&key);
+        while entries.verdicts.len() >= self.capacity {
+            let Some((_, oldest)) = entries.recent.pop_first() else {
+                break;
+            };
+            entries.verdicts.remove(&oldest);
+        }
+
+        let used = entries.touch(key);
+        entries.verdicts.insert(
+            key,
+            Entry {
+                verdict,
+                expires: Instant::now() + self.ttl,
+                used,
+            },
+        );
+    }
+}
+
+fn content_hash(content: &str) -> ContentHash {
+    Sha256::digest(content.as_bytes()).into()
+}
+
+#[async_trait]
+impl ContentFilter for VerdictCache {
+    async fn check(&self, content: String) -> Result<Verdict, handle_errors::Error> {
+        let key = content_hash(&content);
+
+        if let Some(verdict) = self.get(&key) {
+            let hits = self.hits.fetch_add(1, Ordering::Relaxed) + 1;
+            event!(
+                target: "warp-rest-api",
+                Level::DEBUG,
+                hits,
+                misses = self.misses.load(Ordering::Relaxed),
+                "Verdict cache hit"
+            );
+            return Ok(verdict);
+        }
+
+        let misses = self.misses.fetch_add(1, Ordering::Relaxed) + 1;
+        event!(
+            target: "warp-rest-api",
+            Level::DEBUG,
+            hits = self.hits.load(Ordering::Relaxed),
+            misses,
+            "Verdict cache miss"
+        );
+
+        let verdict = self.filter.check(content).await?;
+        if !verdict.fallback {
+            self.insert(key, verdict.clone());
+        }
+        Ok(verdict)
+    }
+}
+
+#[cfg(test)]
+mod cache_tests {
+    use super::{CacheStats, ContentFilter, Verdict, VerdictCache};
+    use async_trait::async_trait;
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    /// Censors everything and counts its calls
+    #[derive(Debug, Default)]
+    struct Counting {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ContentFilter for Counting {
+        async fn check(&self, content: String) -> Result<Verdict, handle_errors::Error> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(Verdict {
+                censored: "*".repeat(content.len()),
+                bad_words: 1,
+                fallback: false,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn check_identical_content_once() {
+        let counting = Arc::new(Counting::default());
+        let cache = VerdictCache::new(counting.clone(), Duration::from_secs(30), 10);
+
+        for _ in 0..3 {
+            let verdict = cache.check("damn".to_string()).await.unwrap();
+            assert_eq!(verdict.censored, "****");
+        }
+        cache.check("hell".to_string()).await.unwrap();
+
+        assert_eq!(counting.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 2,
+                misses: 2,
+                entries: 2
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn check_again_after_ttl() {
+        let counting = Arc::new(Counting::default());
+        let cache = VerdictCache::new(counting.clone(), Duration::from_millis(20), 10);
+
+        cache.check("damn".to_string()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        cache.check("damn".to_string()).await.unwrap();
+
+        assert_eq!(counting.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn evict_oldest_entry_when_full() {
+        let counting = Arc::new(Counting::default());
+        let cache = VerdictCache::new(counting.clone(), Duration::from_secs(30), 2);
+
+        for content in ["one", "two", "three", "three", "two"] {
+            cache.check(content.to_string()).await.unwrap();
+        }
+        assert_eq!(counting.calls.load(Ordering::SeqCst), 3);
+        assert_eq!(cache.stats().entries, 2);
+
+        cache.check("one".to_string()).await.unwrap();
+        assert_eq!(counting.calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn evict_least_recently_used() {
+        let counting = Arc::new(Counting::default());
+        let cache = VerdictCache::new(counting.clone(), Duration::from_secs(30), 2);
+
+        for content in ["one", "two", "one", "three", "one"] {
+            cache.check(content.to_string()).await.unwrap();
+        }
+        assert_eq!(counting.calls.load(Ordering::SeqCst), 3);
+
+        // "two" was used least recently, so "three" took its place
+        cache.check("two".to_string()).await.unwrap();
+        assert_eq!(counting.calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn skip_fallback_verdicts() {
+        #[derive(Debug)]
+        struct Fallback;
+
+        #[async_trait]
+        impl ContentFilter for Fallback {
+            async fn check(&self, content: String) -> Result<Verdict, handle_errors::Error> {
+                Ok(Verdict {
+                    fallback: true,
+                    ..Verdict::clean(content)
+                })
+            }
+        }
+
+        let cache = VerdictCache::new(Arc::new(Fallback), Duration::from_secs(30), 10);
+        cache.check("text".to_string()).await.unwrap();
+        cache.check("text".to_string()).await.unwrap();
+
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 0,
+                misses: 2,
+                entries: 0
+            }
+        );
+    }
+}
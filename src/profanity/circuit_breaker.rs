@@ -0,0 +1,218 @@
+use async_trait::async_trait;
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{event, Level};
+
+use super::{ContentFilter, Verdict};
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Closed {
+        failures: u32,
+    },
+    /// Requests fail fast until the cooldown is over
+    Open {
+        until: Instant,
+    },
+    /// A single probe request decides whether the filter recovered
+    HalfOpen {
+        since: Instant,
+    },
+}
+
+/// Stops calling a filter which keeps failing. While the circuit is
+/// open, requests fail fast or are checked by the fallback filter.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    filter: Arc<dyn ContentFilter>,
+    fallback: Option<Arc<dyn ContentFilter>>,
+    threshold: u32,
+    cooldown: Duration,
+    state: Mutex<State>,
+}
+
+impl CircuitBreaker {
+    /// Opens after `threshold` failures in a row and tries
+    /// the filter again once `cooldown` has passed
+    pub fn new(
+        filter: Arc<dyn ContentFilter>,
+        fallback: Option<Arc<dyn ContentFilter>>,
+        threshold: u32,
+        cooldown: Duration,
+    ) -> Self {
+        CircuitBreaker {
+            filter,
+            fallback,
+            threshold: threshold.max(1),
+            cooldown,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    fn allow_request(&self) -> bool {
+        let mut state = self.state.lock().expect("circuit breaker lock poisoned");
+        let now = Instant::now();
+
+        match *state {
+            State::Closed { .. } => true,
+            State::Open { until } if now >= until => {
+                *state = State::HalfOpen { since: now };
+                true
+            }
+            // The probe may never report back if its request got dropped
+            State::HalfOpen { since } if now >= since + self.cooldown => {
+                *state = State::HalfOpen { since: now };
+                true
+            }
+            State::Open { .. } | State::HalfOpen { .. } => false,
+        }
+    }
+
+    fn record(&self, success: bool) {
+        let mut state = self.state.lock().expect("circuit breaker lock poisoned");
+
+        *state = match (*state, success) {
+            (_, true) => State::Closed { failures: 0 },
+            (State::Closed { failures }, false) if failures + 1 < self.threshold => State::Closed {
+                failures: failures + 1,
+            },
+            (_, false) => {
+                event!(
+                    target: "warp-rest-api",
+                    Level::WARN,
+                    cooldown = ?self.cooldown,
+                    "Content filter keeps failing, circuit opened"
+                );
+                State::Open {
+                    until: Instant::now() + self.cooldown,
+                }
+            }
+        };
+    }
+
+    async fn fallback(&self, content: String) -> Result<Verdict, handle_errors::Error> {
+        match &self.fallback {
+            Some(fallback) => {
+                let verdict = fallback.check(content).await?;
+                Ok(Verdict {
+                    fallback: true,
+                    ..verdict
+                })
+            }
+            None => Err(handle_errors::Error::ModerationUnavailable),
+        }
+    }
+}
+
+/// Rejected requests are not the fault of the provider
+fn is_failure(error: &handle_errors::Error) -> bool {
+    !matches!(error, handle_errors::Error::ClientError(err) if err.status != 429)
+}
+
+#[async_trait]
+impl ContentFilter for CircuitBreaker {
+    async fn check(&self, content: String) -> Result<Verdict, handle_errors::Error> {
+        if !self.allow_request() {
+            return self.fallback(content).await;
+        }
+
+        match self.filter.check(content.clone()).await {
+            Ok(verdict) => {
+                self.record(true);
+                Ok(verdict)
+            }
+            Err(error) if is_failure(&error) => {
+                event!(target: "warp-rest-api", Level::ERROR, "{}", error);
+                self.record(false);
+                self.fallback(content).await
+            }
+            Err(error) => {
+                self.record(true);
+                Err(error)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod circuit_breaker_tests {
+    use super::{CircuitBreaker, ContentFilter, Verdict};
+    use crate::profanity::WordlistFilter;
+    use async_trait::async_trait;
+    use std::{
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    /// Fails while `down` is set and counts its calls
+    #[derive(Debug, Default)]
+    struct Flaky {
+        down: AtomicBool,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ContentFilter for Flaky {
+        async fn check(&self, content: String) -> Result<Verdict, handle_errors::Error> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.down.load(Ordering::SeqCst) {
+                Err(handle_errors::Error::ServerError(
+                    handle_errors::APILayerError {
+                        status: 503,
+                        message: "down".to_string(),
+                    },
+                ))
+            } else {
+                Ok(Verdict::clean(content))
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn fail_fast_once_open() {
+        let flaky = Arc::new(Flaky::default());
+        flaky.down.store(true, Ordering::SeqCst);
+        let breaker = CircuitBreaker::new(flaky.clone(), None, 2, Duration::from_secs(30));
+
+        for _ in 0..4 {
+            assert!(matches!(
+                breaker.check("text".to_string()).await,
+                Err(handle_errors::Error::ModerationUnavailable)
+            ));
+        }
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn use_fallback_while_open() {
+        let flaky = Arc::new(Flaky::default());
+        flaky.down.store(true, Ordering::SeqCst);
+        let fallback = Arc::new(WordlistFilter::new(["damn"]));
+        let breaker = CircuitBreaker::new(flaky, Some(fallback), 1, Duration::from_secs(30));
+
+        let verdict = breaker.check("damn".to_string()).await.unwrap();
+        assert_eq!(verdict.censored, "****");
+        assert!(verdict.fallback);
+    }
+
+    #[tokio::test]
+    async fn close_after_cooldown() {
+        let flaky = Arc::new(Flaky::default());
+        flaky.down.store(true, Ordering::SeqCst);
+        let breaker = CircuitBreaker::new(flaky.clone(), None, 1, Duration::from_millis(20));
+
+        assert!(breaker.check("text".to_string()).await.is_err());
+        flaky.down.store(false, Ordering::SeqCst);
+        assert!(breaker.check("text".to_string()).await.is_err());
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(breaker.check("text".to_string()).await.is_ok());
+        assert!(breaker.check("text".to_string()).await.is_ok());
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 3);
+    }
+}
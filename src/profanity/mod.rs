@@ -0,0 +1,260 @@
+use async_trait::async_trait;
+use futures::future::join_all;
+use std::{fmt::Debug, sync::Arc};
+
+use crate::config::ModerationPolicy;
+
+mod api_layer;
+mod cache;
+mod circuit_breaker;
+mod noop;
+mod wordlist;
+
+pub use api_layer::ApiLayerFilter;
+pub use cache::{CacheStats, VerdictCache};
+pub use circuit_breaker::CircuitBreaker;
+pub use noop::NoopFilter;
+pub use wordlist::WordlistFilter;
+
+/// Backend which checks user content before it gets stored.
+/// The configured filter is shared between all requests, so new
+/// backends only have to implement this trait.
+#[async_trait]
+pub trait ContentFilter: Debug + Send + Sync {
+    async fn check(&self, content: String) -> Result<Verdict, handle_errors::Error>;
+}
+
+/// What a content filter found in the checked content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verdict {
+    /// The content with every profane word censored, or the
+    /// original content if it is clean
+    pub censored: String,
+    pub bad_words: usize,
+    /// Given by a fallback while the filter itself is unavailable
+    pub fallback: bool,
+}
+
+impl Verdict {
+    #[must_use]
+    pub fn clean(content: String) -> Self {
+        Verdict {
+            censored: content,
+            bad_words: 0,
+            fallback: false,
+        }
+    }
+
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.bad_words == 0
+    }
+}
+
+/// Types with text written by users, which is checked field by field
+pub trait TextFields {
+    /// Every text field which has to be checked, with its name
+    fn text_fields(&mut self) -> Vec<(&'static str, &mut String)>;
+}
+
+/// Content which passed moderation and can be stored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Moderated<T> {
+    pub content: T,
+    /// Fields stored uncensored, which have to be reviewed
+    pub flagged: Vec<&'static str>,
+}
+
+impl<T> Moderated<T> {
+    #[must_use]
+    pub fn is_flagged(&self) -> bool {
+        !self.flagged.is_empty()
+    }
+}
+
+/// Applies the configured policy to the verdicts of the content filter
+#[derive(Debug, Clone)]
+pub struct Moderation {
+    filter: Arc<dyn ContentFilter>,
+    policy: ModerationPolicy,
+}
+
+impl Moderation {
+    pub fn new(filter: Arc<dyn ContentFilter>, policy: ModerationPolicy) -> Self {
+        Moderation { filter, policy }
+    }
+
+    /// Checks all text fields of `content` at once
+    ///
+    /// # Errors
+    ///
+    /// If the filter is unavailable, or with `ProfaneContent` if the
+    /// policy rejects profanity and some was found
+    pub async fn moderate<T: TextFields>(
+        &self,
+        content: T,
+    ) -> Result<Moderated<T>, handle_errors::Error> {
+        self.apply(self.policy, content, &[]).await
+    }
+
+    /// Text which is already stored went through the check before,
+    /// so fields which equal their `stored` text are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Like `moderate`
+    pub async fn moderate_changed<T: TextFields>(
+        &self,
+        content: T,
+        stored: &[(&str, &str)],
+    ) -> Result<Moderated<T>, handle_errors::Error> {
+        self.apply(self.policy, content, stored).await
+    }
+
+    /// For content without a review queue, like profiles,
+    /// flagging falls back to censoring.
+    ///
+    /// # Errors
+    ///
+    /// Like `moderate`
+    pub async fn moderate_unreviewed<T: TextFields>(
+        &self,
+        content: T,
+    ) -> Result<T, handle_errors::Error> {
+        let policy = match self.policy {
+            ModerationPolicy::Flag => ModerationPolicy::Censor,
+            policy => policy,
+        };
+
+        self.apply(policy, content, &[])
+            .await
+            .map(|moderated| moderated.content)
+    }
+
+    /// Checks the fields concurrently, so users learn about
+    /// every profane field with a single request.
+    async fn apply<T: TextFields>(
+        &self,
+        policy: ModerationPolicy,
+        mut content: T,
+        stored: &[(&str, &str)],
+    ) -> Result<Moderated<T>, handle_errors::Error> {
+        let mut profane = Vec::new();
+        let mut flagged = Vec::new();
+
+        {
+            let fields: Vec<_> = content
+                .text_fields()
+                .into_iter()
+                .filter(|(name, text)| !stored.contains(&(*name, text.as_str())))
+                .collect();
+            let checks = fields
+                .iter()
+                .map(|(_, text)| self.filter.check((*text).clone()));
+            let verdicts = join_all(checks).await;
+
+            for ((name, text), verdict) in fields.into_iter().zip(verdicts) {
+                let verdict = verdict?;
+                if verdict.is_clean() {
+                    continue;
+                }
+
+                match policy {
+                    ModerationPolicy::Censor => *text = verdict.censored,
+                    ModerationPolicy::Reject => profane.push(name.to_string()),
+                    ModerationPolicy::Flag => flagged.push(name),
+                }
+            }
+        }
+
+        if profane.is_empty() {
+            Ok(Moderated { content, flagged })
+        } else {
+            Err(handle_errors::Error::ProfaneContent(profane))
+        }
+    }
+}
+
+#[cfg(test)]
+mod moderation_tests {
+    use super::{Moderation, WordlistFilter};
+    use crate::{
+        config::ModerationPolicy,
+        types::{account::ProfileUpdate, question::NewQuestion},
+    };
+    use std::sync::Arc;
+
+    fn moderation(policy: ModerationPolicy) -> Moderation {
+        Moderation::new(Arc::new(WordlistFilter::new(["damn"])), policy)
+    }
+
+    fn question(title: &str, content: &str) -> NewQuestion {
+        NewQuestion {
+            title: title.to_string(),
+            content: content.to_string(),
+            tags: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn clean_content_is_kept() {
+        let moderated = moderation(ModerationPolicy::Censor)
+            .moderate(question("a clean title", "a clean sentence"))
+            .await
+            .unwrap();
+
+        assert_eq!(moderated.content.title, "a clean title");
+        assert_eq!(moderated.content.content, "a clean sentence");
+        assert!(!moderated.is_flagged());
+    }
+
+    #[tokio::test]
+    async fn apply_policies() {
+        let censored = moderation(ModerationPolicy::Censor)
+            .moderate(question("title", "damn it"))
+            .await
+            .unwrap();
+        assert_eq!(censored.content.content, "**** it");
+        assert!(!censored.is_flagged());
+
+        let flagged = moderation(ModerationPolicy::Flag)
+            .moderate(question("title", "damn it"))
+            .await
+            .unwrap();
+        assert_eq!(flagged.content.content, "damn it");
+        assert_eq!(flagged.flagged, vec!["content"]);
+
+        let profile = ProfileUpdate {
+            display_name: None,
+            bio: Some("damn it".to_string()),
+            avatar_url: None,
+        };
+        let profile = moderation(ModerationPolicy::Flag)
+            .moderate_unreviewed(profile)
+            .await
+            .unwrap();
+        assert_eq!(profile.bio.as_deref(), Some("**** it"));
+    }
+
+    #[tokio::test]
+    async fn reject_names_every_profane_field() {
+        let rejected = moderation(ModerationPolicy::Reject)
+            .moderate(question("damn", "damn it"))
+            .await;
+
+        assert!(matches!(
+            rejected,
+            Err(handle_errors::Error::ProfaneContent(fields)) if fields == ["title", "content"]
+        ));
+    }
+
+    #[tokio::test]
+    async fn skip_stored_fields() {
+        let moderated = moderation(ModerationPolicy::Flag)
+            .moderate_changed(question("damn", "damn it"), &[("title", "damn")])
+            .await
+            .unwrap();
+
+        assert_eq!(moderated.flagged, vec!["content"]);
+    }
+}
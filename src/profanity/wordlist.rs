@@ -0,0 +1,309 @@
+use async_trait::async_trait;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::Path,
+    sync::Arc,
+};
+
+use super::{ContentFilter, Verdict};
+
+/// Censors words from local wordlists, works without any external API.
+/// Words are compared after normalization, so `ShIiIt`, `sh1t` and
+/// `$hit` all match a listed `shit`. Letters may be repeated more often
+/// than in the listed word, but not less, so a listed `ass` does not
+/// match `as`.
+#[derive(Clone)]
+pub struct WordlistFilter {
+    /// The words of every active list, keyed by language
+    lists: Arc<BTreeMap<String, List>>,
+}
+
+/// The run lengths of the listed words, keyed by their letters
+/// without repeats, e.g. `[1, 2]` under `as` for `ass`
+type List = HashMap<String, Vec<Vec<usize>>>;
+
+impl WordlistFilter {
+    /// A filter with a single list which belongs to no language
+    pub fn new<I>(words: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        WordlistFilter {
+            lists: Arc::new(BTreeMap::from([(String::new(), list(words))])),
+        }
+    }
+
+    /// Reads one word per line, lines starting with `#` are comments
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be read
+    pub fn from_file(path: &Path) -> Result<Self, handle_errors::Error> {
+        Ok(WordlistFilter::new(read_list(path)?))
+    }
+
+    /// Loads the `<language>.txt` files of the directory, e.g. `en.txt`,
+    /// for the given languages, or all of them if none are given
+    ///
+    /// # Errors
+    ///
+    /// If the directory or one of its lists cannot be read, or a
+    /// language has no list
+    pub fn from_dir(dir: &Path, languages: &[String]) -> Result<Self, handle_errors::Error> {
+        let mut available = BTreeMap::new();
+        for entry in fs::read_dir(dir).map_err(handle_errors::Error::WordlistError)? {
+            let path = entry.map_err(handle_errors::Error::WordlistError)?.path();
+            if path.extension().is_none_or(|extension| extension != "txt") {
+                continue;
+            }
+            if let Some(language) = path.file_stem() {
+                available.insert(language.to_string_lossy().into_owned(), path);
+            }
+        }
+
+        if let Some(missing) = languages
+            .iter()
+            .find(|language| !available.contains_key(*language))
+        {
+            return Err(handle_errors::Error::WordlistError(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no {missing}.txt in {}", dir.display()),
+            )));
+        }
+
+        let mut lists = BTreeMap::new();
+        for (language, path) in available {
+            if languages.is_empty() || languages.contains(&language) {
+                lists.insert(language, list(read_list(&path)?));
+            }
+        }
+
+        Ok(WordlistFilter {
+            lists: Arc::new(lists),
+        })
+    }
+
+    /// The languages whose lists are checked
+    #[must_use]
+    pub fn languages(&self) -> Vec<&str> {
+        self.lists
+            .keys()
+            .map(String::as_str)
+            .filter(|language| !language.is_empty())
+            .collect()
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        let (letters, runs) = normalize(word);
+        self.lists
+            .values()
+            .filter_map(|words| words.get(&letters))
+            .flatten()
+            .any(|listed| listed.iter().zip(&runs).all(|(listed, run)| run >= listed))
+    }
+
+    /// Replaces every character of a listed word with `*`, like the
+    /// `censored_content` of the `APILayer` bad words API
+    #[must_use]
+    pub fn censor(&self, content: &str) -> String {
+        self.verdict(content).censored
+    }
+
+    #[must_use]
+    pub fn verdict(&self, content: &str) -> Verdict {
+        let mut censored = String::with_capacity(content.len());
+        let mut word = String::new();
+        let mut bad_words = 0;
+
+        for c in content.chars().chain(std::iter::once(' ')) {
+            if is_word_char(c) {
+                word.push(c);
+                continue;
+            }
+
+            if self.contains(&word) {
+                bad_words += 1;
+                censored.extend(std::iter::repeat_n('*', word.chars().count()));
+            } else {
+                censored.push_str(&word);
+            }
+            word.clear();
+            censored.push(c);
+        }
+
+        // Drop the separator which flushed the last word
+        censored.pop();
+        Verdict {
+            censored,
+            bad_words,
+            fallback: false,
+        }
+    }
+}
+
+// The word set would end up in the log of every request
+impl std::fmt::Debug for WordlistFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("WordlistFilter")
+            .field("languages", &self.languages())
+            .field(
+                "words",
+                &self
+                    .lists
+                    .values()
+                    .flat_map(HashMap::values)
+                    .map(Vec::len)
+                    .sum::<usize>(),
+            )
+            .finish()
+    }
+}
+
+#[async_trait]
+impl ContentFilter for WordlistFilter {
+    async fn check(&self, content: String) -> Result<Verdict, handle_errors::Error> {
+        Ok(self.verdict(&content))
+    }
+}
+
+fn list<I>(words: I) -> List
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut list = List::new();
+    for word in words {
+        let (letters, runs) = normalize(word.as_ref().trim());
+        if !letters.is_empty() {
+            list.entry(letters).or_default().push(runs);
+        }
+    }
+    list
+}
+
+/// The words of a file, without its comments
+fn read_list(path: &Path) -> Result<Vec<String>, handle_errors::Error> {
+    let list = fs::read_to_string(path).map_err(handle_errors::Error::WordlistError)?;
+
+    Ok(list
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '@' || c == '$'
+}
+
+/// Lowercases and undoes leetspeak, then splits the word into its
+/// characters without repeats and how often each one is repeated
+fn normalize(word: &str) -> (String, Vec<usize>) {
+    let mut letters = String::with_capacity(word.len());
+    let mut runs: Vec<usize> = Vec::new();
+
+    for c in word.chars().flat_map(char::to_lowercase).map(unleet) {
+        match runs.last_mut() {
+            Some(run) if letters.ends_with(c) => *run += 1,
+            _ => {
+                letters.push(c);
+                runs.push(1);
+            }
+        }
+    }
+
+    (letters, runs)
+}
+
+fn unleet(c: char) -> char {
+    match c {
+        '0' => 'o',
+        '1' => 'i',
+        '3' => 'e',
+        '4' | '@' => 'a',
+        '5' | '$' => 's',
+        '7' => 't',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod wordlist_tests {
+    use super::WordlistFilter;
+    use std::path::Path;
+
+    #[test]
+    fn censor_listed_words() {
+        let filter = WordlistFilter::new(["shitty", "damn"]);
+
+        assert_eq!(
+            filter.censor("This is a Shitty sentence, damn!"),
+            "This is a ****** sentence, ****!"
+        );
+    }
+
+    #[test]
+    fn keep_clean_content() {
+        let filter = WordlistFilter::new(["damn"]);
+
+        assert_eq!(filter.censor("damnation is fine"), "damnation is fine");
+        assert_eq!(filter.censor(""), "");
+    }
+
+    #[test]
+    fn normalize_obfuscated_words() {
+        let filter = WordlistFilter::new(["shit"]);
+
+        assert_eq!(
+            filter.censor("SHIIIIT, sh1t and $h1t"),
+            "*******, **** and ****"
+        );
+        assert_eq!(filter.censor("$hiiit happens"), "****** happens");
+    }
+
+    #[test]
+    fn keep_words_with_fewer_repeats() {
+        let filter = WordlistFilter::new(["ass", "butt", "piss", "bollocks"]);
+
+        // Clean words which only differ from a listed one by a repeat
+        assert_eq!(
+            filter.censor("as good as it gets, but not yet"),
+            "as good as it gets, but not yet"
+        );
+        assert_eq!(filter.censor("pis bolocks"), "pis bolocks");
+        assert_eq!(
+            filter.censor("asss, buttt, piiisss and bollloccks"),
+            "****, *****, ******* and **********"
+        );
+    }
+
+    #[test]
+    fn load_all_languages() {
+        let filter = WordlistFilter::from_dir(Path::new("wordlists"), &[]).unwrap();
+
+        assert_eq!(filter.languages(), ["de", "en"]);
+        assert_eq!(
+            filter.censor("Scheisse, what a bitch"),
+            "********, what a *****"
+        );
+    }
+
+    #[test]
+    fn load_chosen_languages() {
+        let filter = WordlistFilter::from_dir(Path::new("wordlists"), &["en".to_string()]).unwrap();
+
+        assert_eq!(filter.languages(), ["en"]);
+        assert_eq!(
+            filter.censor("Scheisse, what a bitch"),
+            "Scheisse, what a *****"
+        );
+
+        let missing = WordlistFilter::from_dir(Path::new("wordlists"), &["fr".to_string()]);
+        assert!(matches!(
+            missing,
+            Err(handle_errors::Error::WordlistError(_))
+        ));
+    }
+}
@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt::Debug, sync::Arc};
+
+/// What an account contributed so far, the input of every reputation rule
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Contributions {
+    /// Questions which are not deleted
+    pub questions_asked: i64,
+    pub answers_given: i64,
+    /// Answers whose question was not deleted
+    pub answers_on_live_questions: i64,
+}
+
+/// A single signal which adds to (or takes from) the reputation of an account.
+/// New signals are added by implementing this trait and registering the rule
+/// in `ReputationRules`.
+pub trait ReputationRule: Debug + Send + Sync {
+    fn points(&self, contributions: &Contributions) -> i64;
+}
+
+/// Points for every question which is still online
+#[derive(Debug)]
+pub struct QuestionsAsked(pub i64);
+
+impl ReputationRule for QuestionsAsked {
+    fn points(&self, contributions: &Contributions) -> i64 {
+        self.0 * contributions.questions_asked
+    }
+}
+
+/// Points for every answer given
+#[derive(Debug)]
+pub struct AnswersGiven(pub i64);
+
+impl ReputationRule for AnswersGiven {
+    fn points(&self, contributions: &Contributions) -> i64 {
+        self.0 * contributions.answers_given
+    }
+}
+
+/// Extra points for answers whose question stays online
+#[derive(Debug)]
+pub struct AnswersOnLiveQuestions(pub i64);
+
+impl ReputationRule for AnswersOnLiveQuestions {
+    fn points(&self, contributions: &Contributions) -> i64 {
+        self.0 * contributions.answers_on_live_questions
+    }
+}
+
+/// The rule set the reputation of every account is computed with
+#[derive(Debug, Clone)]
+pub struct ReputationRules {
+    rules: Arc<Vec<Box<dyn ReputationRule>>>,
+}
+
+impl ReputationRules {
+    pub fn new(rules: Vec<Box<dyn ReputationRule>>) -> Self {
+        ReputationRules {
+            rules: Arc::new(rules),
+        }
+    }
+
+    /// Sum of all rules, reputation never drops below zero
+    #[must_use]
+    pub fn score(&self, contributions: &Contributions) -> i64 {
+        self.rules
+            .iter()
+            .map(|rule| rule.points(contributions))
+            .sum::<i64>()
+            .max(0)
+    }
+}
+
+impl Default for ReputationRules {
+    /// Keep in sync with the backfill in the `reputation` migration
+    fn default() -> Self {
+        ReputationRules::new(vec![
+            Box::new(QuestionsAsked(5)),
+            Box::new(AnswersGiven(2)),
+            Box::new(AnswersOnLiveQuestions(8)),
+        ])
+    }
+}
+
+/// Actions which are unlocked by reputation instead of ownership
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Privilege {
+    EditOthersPosts,
+}
+
+impl Privilege {
+    pub const ALL: [Privilege; 1] = [Privilege::EditOthersPosts];
+
+    /// Reputation needed to be granted the privilege
+    #[must_use]
+    pub fn threshold(self) -> i64 {
+        match self {
+            Privilege::EditOthersPosts => 1000,
+        }
+    }
+
+    #[must_use]
+    pub fn is_granted(self, reputation: i64) -> bool {
+        reputation >= self.threshold()
+    }
+}
+
+/// Body of `GET /users/{id}/reputation`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reputation {
+    pub score: i64,
+    pub privileges: Vec<Privilege>,
+}
+
+impl Reputation {
+    #[must_use]
+    pub fn new(score: i64) -> Self {
+        Reputation {
+            score,
+            privileges: Privilege::ALL
+                .into_iter()
+                .filter(|privilege| privilege.is_granted(score))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod reputation_tests {
+    use super::{Contributions, Privilege, Reputation, ReputationRule, ReputationRules};
+
+    #[test]
+    fn default_rules() {
+        let contributions = Contributions {
+            questions_asked: 2,
+            answers_given: 3,
+            answers_on_live_questions: 1,
+        };
+
+        assert_eq!(ReputationRules::default().score(&contributions), 24);
+        assert_eq!(
+            ReputationRules::default().score(&Contributions::default()),
+            0
+        );
+    }
+
+    #[derive(Debug)]
+    struct Penalty;
+
+    impl ReputationRule for Penalty {
+        fn points(&self, _: &Contributions) -> i64 {
+            -50
+        }
+    }
+
+    #[test]
+    fn custom_rules_never_go_negative() {
+        let rules = ReputationRules::new(vec![Box::new(Penalty)]);
+        assert_eq!(rules.score(&Contributions::default()), 0);
+    }
+
+    #[test]
+    fn privileges_follow_thresholds() {
+        let threshold = Privilege::EditOthersPosts.threshold();
+
+        assert!(Reputation::new(threshold - 1).privileges.is_empty());
+        assert_eq!(
+            Reputation::new(threshold).privileges,
+            vec![Privilege::EditOthersPosts]
+        );
+    }
+}
@@ -3,7 +3,7 @@ use tracing::{event, instrument, Level};
 use warp::http::StatusCode;
 
 use crate::{
-    profanity::check_profanity,
+    profanity::Moderation,
     store::Store,
     types::{account::Session, answer::Answer},
 };
@@ -12,20 +12,25 @@ use crate::{
 pub async fn add_answer(
     session: Session,
     store: Store,
+    moderation: Moderation,
     params: HashMap<String, String>,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
-    let content = match check_profanity(params.get("content").unwrap().to_string()).await {
-        Ok(res) => res,
-        Err(e) => return Err(warp::reject::custom(e)),
-    };
-
     let answer = Answer {
-        content,
+        content: params.get("content").unwrap().clone(),
         question_id: params.get("questionId").unwrap().parse().unwrap(),
     };
 
-    match store.add_answer(answer, account_id).await {
+    let moderated = match moderation.moderate(answer).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    let flagged = moderated.is_flagged();
+
+    match store
+        .add_answer(moderated.content, account_id, flagged)
+        .await
+    {
         Ok(_) => {
             event!(target: "warp-rest-api", Level::INFO, "POST NEW Answer");
             Ok(warp::reply::with_status("Answer added", StatusCode::OK))
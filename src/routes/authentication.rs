@@ -6,7 +6,7 @@ use tracing::{event, instrument, Level};
 use warp::{http::StatusCode, Filter};
 
 use crate::store::Store;
-use crate::types::account::{Account, AccountId, Session};
+use crate::types::account::{Account, AccountId, Credentials, Session};
 
 #[instrument]
 pub async fn register(store: Store, account: Account) -> Result<impl warp::Reply, warp::Rejection> {
@@ -31,13 +31,15 @@ pub async fn register(store: Store, account: Account) -> Result<impl warp::Reply
 pub async fn login(store: Store, login: Account) -> Result<impl warp::Reply, warp::Rejection> {
     event!(target: "warp-rest-api", Level::WARN, "LOGIN ATTEMPT");
     match store.get_account(login.email).await {
-        Ok(account) => match verify_password(&account.password, login.password.as_bytes()) {
+        Ok(Credentials {
+            id,
+            password: Some(hash),
+            ..
+        }) => match argon2::verify_encoded(&hash, login.password.as_bytes()) {
             Ok(verified) => {
                 if verified {
                     event!(target: "warp-rest-api", Level::INFO, "ISSUE LOGIN TOKEN");
-                    Ok(warp::reply::json(&issue_token(
-                        account.id.expect("id not found"),
-                    )))
+                    Ok(warp::reply::json(&issue_token(&id)))
                 } else {
                     Err(warp::reject::custom(handle_errors::Error::WrongPassword))
                 }
@@ -46,14 +48,28 @@ pub async fn login(store: Store, login: Account) -> Result<impl warp::Reply, war
                 handle_errors::Error::ArgonLibraryError(e),
             )),
         },
+        // Accounts imported without their password hash cannot log in. Like
+        // an unknown email, they must not be told apart from a wrong password.
+        Ok(Credentials { password: None, .. }) | Err(handle_errors::Error::NotFound) => {
+            Err(warp::reject::custom(handle_errors::Error::WrongPassword))
+        }
         Err(e) => Err(warp::reject::custom(e)),
     }
 }
 
-pub fn verify_token(token: String) -> Result<Session, handle_errors::Error> {
+/// The session a token was issued for
+///
+/// # Errors
+///
+/// With `CannotDecryptToken` if the token is invalid or expired
+///
+/// # Panics
+///
+/// If `PASETO_KEY` is not set
+pub fn verify_token(token: &str) -> Result<Session, handle_errors::Error> {
     let key = env::var("PASETO_KEY").unwrap();
     let token = paseto::tokens::validate_local_token(
-        &token,
+        token,
         None,
         key.as_bytes(),
         &paseto::tokens::TimeBackend::Chrono,
@@ -63,17 +79,13 @@ pub fn verify_token(token: String) -> Result<Session, handle_errors::Error> {
     serde_json::from_value::<Session>(token).map_err(|_| handle_errors::Error::CannotDecryptToken)
 }
 
-fn hash_password(password: &[u8]) -> String {
+pub fn hash_password(password: &[u8]) -> String {
     let salt = rand::thread_rng().gen::<[u8; 32]>();
     let config = Config::default();
     argon2::hash_encoded(password, &salt, &config).unwrap()
 }
 
-fn verify_password(hash: &str, password: &[u8]) -> Result<bool, argon2::Error> {
-    argon2::verify_encoded(hash, password)
-}
-
-fn issue_token(account_id: AccountId) -> String {
+pub(crate) fn issue_token(account_id: &AccountId) -> String {
     let key = env::var("PASETO_KEY").unwrap();
     let current_date_time = Utc::now();
     let dt = current_date_time + chrono::Duration::days(1);
@@ -89,23 +101,38 @@ fn issue_token(account_id: AccountId) -> String {
 
 pub fn auth() -> impl Filter<Extract = (Session,), Error = warp::Rejection> + Clone {
     warp::header::<String>("Authorization").and_then(|token: String| {
-        let token = match verify_token(token) {
-            Ok(t) => t,
-            Err(_) => return future::ready(Err(warp::reject::reject())),
+        let Ok(token) = verify_token(&token) else {
+            return future::ready(Err(warp::reject::reject()));
         };
 
         future::ready(Ok(token))
     })
 }
 
+/// Like `auth`, but lets requests without a valid token through
+/// anonymously, so a stale token still gets the public view
+pub fn optional_auth() -> impl Filter<Extract = (Option<Session>,), Error = warp::Rejection> + Clone
+{
+    warp::header::optional::<String>("Authorization").and_then(|token: Option<String>| {
+        future::ready(Ok::<_, warp::Rejection>(
+            token.and_then(|token| verify_token(&token).ok()),
+        ))
+    })
+}
+
 #[cfg(test)]
 mod authentication_tests {
-    use super::{auth, env, issue_token, AccountId};
+    use super::{auth, AccountId};
+    use crate::{
+        routes::test_helpers::{set_paseto_key, test_routes, token_for},
+        store::memory::MemoryStore,
+    };
+    use std::sync::Arc;
+    use warp::http::StatusCode;
 
     #[tokio::test]
     async fn post_questions_auth() {
-        env::set_var("PASETO_KEY", "RANDOM WORDS WINTER MACINTOSH PC");
-        let token = issue_token(AccountId(3));
+        let token = token_for(&AccountId(3));
 
         let filter = auth();
 
@@ -115,4 +142,81 @@ mod authentication_tests {
 
         assert_eq!(res.await.unwrap().account_id, AccountId(3));
     }
+
+    #[tokio::test]
+    async fn register_twice_and_login_unknown_account() {
+        let routes = test_routes(Arc::new(MemoryStore::default()));
+        let account = serde_json::json!({"email": "user@email.com", "password": "secret"});
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/registration")
+            .json(&account)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/registration")
+            .json(&account)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/login")
+            .json(&serde_json::json!({"email": "unknown@email.com", "password": "secret"}))
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn emails_ignore_case() {
+        set_paseto_key();
+        let routes = test_routes(Arc::new(MemoryStore::default()));
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/registration")
+            .json(&serde_json::json!({"email": "User@email.com", "password": "secret"}))
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/registration")
+            .json(&serde_json::json!({"email": "user@EMAIL.com", "password": "other"}))
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/login")
+            .json(&serde_json::json!({"email": "user@email.com", "password": "secret"}))
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn accounts_without_password_cannot_log_in() {
+        let store = Arc::new(MemoryStore::default());
+        store.create_account("imported@email.com");
+        let routes = test_routes(store);
+
+        for password in ["", "secret"] {
+            let res = warp::test::request()
+                .method("POST")
+                .path("/login")
+                .json(&serde_json::json!({"email": "imported@email.com", "password": password}))
+                .reply(&routes)
+                .await;
+            assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        }
+    }
 }
@@ -1,3 +1,10 @@
 pub mod answer;
 pub mod authentication;
+pub mod moderation;
+pub mod profile;
 pub mod question;
+pub mod report;
+pub mod transfer;
+
+#[cfg(test)]
+mod test_helpers;
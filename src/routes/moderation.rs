@@ -0,0 +1,396 @@
+use tracing::{event, instrument, Level};
+
+use crate::{
+    store::Store,
+    types::{
+        account::{AccountId, Session},
+        report::{ReportAction, ReportResolution, ReportTarget},
+    },
+};
+
+#[instrument]
+pub async fn get_queue(
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    ensure_moderator(&store, &session.account_id).await?;
+
+    match store.get_moderation_queue().await {
+        Ok(queue) => {
+            event!(target: "warp-rest-api", Level::INFO, "GET Moderation queue");
+            Ok(warp::reply::json(&queue))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[instrument]
+pub async fn approve_question(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    decide_question(id, ReportAction::Keep, session, store).await
+}
+
+#[instrument]
+pub async fn reject_question(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    decide_question(id, ReportAction::Remove, session, store).await
+}
+
+#[instrument]
+pub async fn approve_answer(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    decide_answer(id, ReportAction::Keep, session, store).await
+}
+
+#[instrument]
+pub async fn reject_answer(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    decide_answer(id, ReportAction::Remove, session, store).await
+}
+
+/// Pending and rejected posts of the logged in account
+#[instrument]
+pub async fn get_own_posts(
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match store.get_unapproved_posts(&session.account_id).await {
+        Ok(posts) => {
+            event!(target: "warp-rest-api", Level::INFO, "GET Unapproved posts");
+            Ok(warp::reply::json(&posts))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[instrument]
+pub async fn get_reports(
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    ensure_moderator(&store, &session.account_id).await?;
+
+    match store.get_open_reports().await {
+        Ok(reports) => {
+            event!(target: "warp-rest-api", Level::INFO, "GET Open reports");
+            Ok(warp::reply::json(&reports))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[instrument]
+pub async fn resolve_report(
+    id: i32,
+    session: Session,
+    store: Store,
+    resolution: ReportResolution,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    ensure_moderator(&store, &account_id).await?;
+
+    match store
+        .resolve_report(id, resolution.action, account_id)
+        .await
+    {
+        Ok(reports) => {
+            event!(target: "warp-rest-api", Level::INFO, "RESOLVE Report");
+            Ok(warp::reply::json(&reports))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/// Sets the status of the question and resolves its open reports with
+/// the same decision, so they do not stay in the report queue
+async fn decide_question(
+    id: i32,
+    action: ReportAction,
+    session: Session,
+    store: Store,
+) -> Result<warp::reply::Json, warp::Rejection> {
+    let account_id = session.account_id;
+    ensure_moderator(&store, &account_id).await?;
+
+    let decided = store
+        .transaction(|store| async move {
+            let question = store.set_question_status(id, action.status()).await?;
+            store
+                .resolve_reports(ReportTarget::Question(id), action, account_id)
+                .await?;
+            Ok(question)
+        })
+        .await;
+
+    match decided {
+        Ok(question) => {
+            event!(target: "warp-rest-api", Level::INFO, status = action.status().as_str(), "MODERATE Question");
+            Ok(warp::reply::json(&question))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/// Like [`decide_question`], for answers
+async fn decide_answer(
+    id: i32,
+    action: ReportAction,
+    session: Session,
+    store: Store,
+) -> Result<warp::reply::Json, warp::Rejection> {
+    let account_id = session.account_id;
+    ensure_moderator(&store, &account_id).await?;
+
+    let decided = store
+        .transaction(|store| async move {
+            let answer = store.set_answer_status(id, action.status()).await?;
+            store
+                .resolve_reports(ReportTarget::Answer(id), action, account_id)
+                .await?;
+            Ok(answer)
+        })
+        .await;
+
+    match decided {
+        Ok(answer) => {
+            event!(target: "warp-rest-api", Level::INFO, status = action.status().as_str(), "MODERATE Answer");
+            Ok(warp::reply::json(&answer))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+pub(crate) async fn ensure_moderator(
+    store: &Store,
+    account_id: &AccountId,
+) -> Result<(), handle_errors::Error> {
+    if store.is_moderator(account_id).await? {
+        Ok(())
+    } else {
+        Err(handle_errors::Error::Unauthorized)
+    }
+}
+
+#[cfg(test)]
+mod moderation_tests {
+    use crate::{
+        routes::test_helpers::{moderator_token, test_routes, token},
+        store::memory::MemoryStore,
+        types::report::ReportThreshold,
+    };
+    use std::sync::Arc;
+    use warp::http::StatusCode;
+
+    #[tokio::test]
+    async fn approve_flagged_question() {
+        let store = Arc::new(MemoryStore::default());
+        let author = token(&store, "author@email.com");
+        let moderator = moderator_token(&store, "moderator@email.com");
+        let routes = test_routes(store);
+
+        warp::test::request()
+            .method("POST")
+            .path("/questions")
+            .header("Authorization", &author)
+            .json(&serde_json::json!({"title": "Title", "content": "damn"}))
+            .reply(&routes)
+            .await;
+
+        let res = warp::test::request()
+            .path("/moderation/queue")
+            .header("Authorization", &author)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = warp::test::request()
+            .path("/moderation/queue")
+            .header("Authorization", &moderator)
+            .reply(&routes)
+            .await;
+        let queue: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(queue["questions"][0]["content"], "damn");
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/moderation/questions/1/approve")
+            .header("Authorization", &moderator)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = warp::test::request()
+            .path("/users/1/reputation")
+            .reply(&routes)
+            .await;
+        let reputation: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(reputation["score"], 5);
+
+        let res = warp::test::request()
+            .path("/questions")
+            .reply(&routes)
+            .await;
+        let questions: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(questions[0]["moderation_status"], "approved");
+
+        let res = warp::test::request()
+            .path("/moderation/queue")
+            .header("Authorization", &moderator)
+            .reply(&routes)
+            .await;
+        let queue: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(queue, serde_json::json!({"questions": [], "answers": []}));
+    }
+
+    #[tokio::test]
+    async fn reject_flagged_posts() {
+        let store = Arc::new(MemoryStore::default());
+        let author = token(&store, "author@email.com");
+        let moderator = moderator_token(&store, "moderator@email.com");
+        let routes = test_routes(store);
+
+        warp::test::request()
+            .method("POST")
+            .path("/questions")
+            .header("Authorization", &author)
+            .json(&serde_json::json!({"title": "Title", "content": "Content"}))
+            .reply(&routes)
+            .await;
+        warp::test::request()
+            .method("POST")
+            .path("/answers")
+            .header("Authorization", &author)
+            .json(&serde_json::json!({"content": "damn", "questionId": "1"}))
+            .reply(&routes)
+            .await;
+
+        let res = warp::test::request()
+            .path("/moderation/queue")
+            .header("Authorization", &moderator)
+            .reply(&routes)
+            .await;
+        let queue: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(queue["questions"], serde_json::json!([]));
+        assert_eq!(queue["answers"][0]["content"], "damn");
+
+        for path in [
+            "/moderation/answers/1/reject",
+            "/moderation/questions/1/reject",
+        ] {
+            let res = warp::test::request()
+                .method("POST")
+                .path(path)
+                .header("Authorization", &author)
+                .reply(&routes)
+                .await;
+            assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+            let res = warp::test::request()
+                .method("POST")
+                .path(path)
+                .header("Authorization", &moderator)
+                .reply(&routes)
+                .await;
+            assert_eq!(res.status(), StatusCode::OK);
+            let post: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+            assert_eq!(post["moderation_status"], "rejected");
+        }
+
+        let res = warp::test::request()
+            .path("/questions/1")
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let res = warp::test::request()
+            .path("/account/moderation")
+            .header("Authorization", &author)
+            .reply(&routes)
+            .await;
+        let own: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(own["questions"][0]["moderation_status"], "rejected");
+        assert_eq!(own["answers"][0]["moderation_status"], "rejected");
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/moderation/questions/2/approve")
+            .header("Authorization", &moderator)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn queue_decisions_resolve_reports() {
+        let store = Arc::new(MemoryStore::default().with_report_threshold(ReportThreshold(1)));
+        let author = token(&store, "author@email.com");
+        let reporter = token(&store, "reporter@email.com");
+        let moderator = moderator_token(&store, "moderator@email.com");
+        let routes = test_routes(store);
+
+        warp::test::request()
+            .method("POST")
+            .path("/questions")
+            .header("Authorization", &author)
+            .json(&serde_json::json!({"title": "Title", "content": "Content"}))
+            .reply(&routes)
+            .await;
+        warp::test::request()
+            .method("POST")
+            .path("/answers")
+            .header("Authorization", &author)
+            .json(&serde_json::json!({"content": "Answer", "questionId": "1"}))
+            .reply(&routes)
+            .await;
+
+        for (report, decide, status) in [
+            (
+                "/questions/1/report",
+                "/moderation/questions/1/approve",
+                "approved",
+            ),
+            (
+                "/answers/1/report",
+                "/moderation/answers/1/reject",
+                "rejected",
+            ),
+        ] {
+            let res = warp::test::request()
+                .method("POST")
+                .path(report)
+                .header("Authorization", &reporter)
+                .json(&serde_json::json!({"reason": "spam"}))
+                .reply(&routes)
+                .await;
+            assert_eq!(res.status(), StatusCode::CREATED);
+
+            let res = warp::test::request()
+                .method("POST")
+                .path(decide)
+                .header("Authorization", &moderator)
+                .reply(&routes)
+                .await;
+            let post: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+            assert_eq!(post["moderation_status"], status);
+
+            let res = warp::test::request()
+                .path("/moderation/reports")
+                .header("Authorization", &moderator)
+                .reply(&routes)
+                .await;
+            assert_eq!(res.body().as_ref(), b"[]");
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use tracing::{event, instrument, Level};
+
+use crate::{
+    profanity::Moderation,
+    reputation::Reputation,
+    store::Store,
+    types::account::{ProfileUpdate, Session},
+};
+
+#[instrument]
+pub async fn get_profile(id: i32, store: Store) -> Result<impl warp::Reply, warp::Rejection> {
+    match store.get_profile(id).await {
+        Ok(profile) => {
+            event!(target: "warp-rest-api", Level::INFO, "GET Profile");
+            Ok(warp::reply::json(&profile))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[instrument]
+pub async fn get_reputation(id: i32, store: Store) -> Result<impl warp::Reply, warp::Rejection> {
+    match store.get_reputation(id).await {
+        Ok(score) => {
+            event!(target: "warp-rest-api", Level::INFO, "GET Reputation");
+            Ok(warp::reply::json(&Reputation::new(score)))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[instrument]
+pub async fn update_profile(
+    session: Session,
+    store: Store,
+    moderation: Moderation,
+    profile: ProfileUpdate,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+
+    // Avatars are rendered by clients, so only web URLs are accepted
+    if let Some(url) = &profile.avatar_url {
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            return Err(warp::reject::custom(handle_errors::Error::InvalidInput(
+                "avatar_url has to be a http(s) URL".to_string(),
+            )));
+        }
+    }
+
+    let profile = moderation.moderate_unreviewed(profile).await?;
+
+    match store.update_profile(account_id.clone(), profile).await {
+        Ok(true) => {
+            event!(target: "warp-rest-api", Level::INFO, "UPDATE Profile");
+            let profile = store.get_profile(account_id.0).await?;
+            Ok(warp::reply::json(&profile))
+        }
+        Ok(false) => Err(warp::reject::custom(handle_errors::Error::NotFound)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
@@ -1,15 +1,21 @@
 use tracing::{event, instrument, Level};
 
 use std::collections::HashMap;
-use warp::http::StatusCode;
+use warp::{
+    http::{header::ETAG, StatusCode},
+    Reply,
+};
 
 use crate::{
-    profanity::check_profanity,
+    profanity::Moderation,
+    reputation::Privilege,
     store::Store,
     types::{
-        account::Session,
+        account::{AccountId, Session},
+        etag,
+        moderation::ModerationStatus,
         pagination::{extract_pagination, Pagination},
-        question::{NewQuestion, Question},
+        question::{NewQuestion, Question, QuestionPatch},
     },
 };
 
@@ -17,26 +23,17 @@ use crate::{
 pub async fn add_question(
     session: Session,
     store: Store,
+    moderation: Moderation,
     new_question: NewQuestion,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
-    let title = match check_profanity(new_question.title).await {
-        Ok(res) => res,
-        Err(e) => return Err(warp::reject::custom(e)),
-    };
+    let moderated = moderation.moderate(new_question).await?;
+    let flagged = moderated.is_flagged();
 
-    let content = match check_profanity(new_question.content).await {
-        Ok(res) => res,
-        Err(e) => return Err(warp::reject::custom(e)),
-    };
-
-    let question = NewQuestion {
-        title,
-        content,
-        tags: new_question.tags,
-    };
-
-    match store.add_question(question, account_id).await {
+    match store
+        .add_question(moderated.content, account_id, flagged)
+        .await
+    {
         Ok(question) => {
             event!(target: "warp-rest-api", Level::INFO, "POST NEW Question");
             Ok(warp::reply::json(&question))
@@ -61,7 +58,7 @@ pub async fn get_questions(
         // we override our mutable variable from above
         // and replace it with the given Pagination
         // from the client.
-        pagination = extract_pagination(params)?;
+        pagination = extract_pagination(&params)?;
     }
 
     match store
@@ -79,74 +76,592 @@ pub async fn get_questions(
 #[instrument]
 pub async fn get_question_by_id(
     id: i32,
+    if_none_match: Option<String>,
+    session: Option<Session>,
     store: Store,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     match store.get_question_by_id(id).await {
+        Ok(res) if !may_view(&res, session.as_ref(), &store).await? => {
+            Err(warp::reject::custom(handle_errors::Error::NotFound))
+        }
         Ok(res) => {
+            let tag = etag::from_version(res.version);
+            if if_none_match
+                .is_some_and(|header| etag::parse_if_none_match(&header).matches(res.version))
+            {
+                event!(target: "warp-rest-api", Level::INFO, "GET Question by ID not modified");
+                let reply = warp::reply::with_status(warp::reply(), StatusCode::NOT_MODIFIED);
+                return Ok(warp::reply::with_header(reply, ETAG, tag).into_response());
+            }
+
             event!(target: "warp-rest-api", Level::INFO, "GET Question by ID");
-            Ok(warp::reply::json(&res))
+            Ok(warp::reply::with_header(warp::reply::json(&res), ETAG, tag).into_response())
         }
         Err(e) => Err(warp::reject::custom(e)),
     }
 }
 
+/// Questions which are not approved, because they were flagged, hidden
+/// by reports or rejected, are only shown to their author and moderators
+async fn may_view(
+    question: &Question,
+    session: Option<&Session>,
+    store: &Store,
+) -> Result<bool, handle_errors::Error> {
+    if question.moderation_status == ModerationStatus::Approved {
+        return Ok(true);
+    }
+
+    match session {
+        Some(session) if session.account_id == question.author.id => Ok(true),
+        Some(session) => store.is_moderator(&session.account_id).await,
+        None => Ok(false),
+    }
+}
+
 #[instrument]
 pub async fn update_question(
     id: i32,
+    if_match: Option<String>,
     session: Session,
     store: Store,
-    question: Question,
+    moderation: Moderation,
+    question: NewQuestion,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
-    if store.is_question_owner(id, &account_id).await? {
-        let title = check_profanity(question.title);
-        let content = check_profanity(question.content);
-
-        let (title, content) = tokio::join!(title, content);
-
-        if title.is_ok() && content.is_ok() {
-            let question = Question {
-                id: question.id,
-                title: title.unwrap(),
-                content: content.unwrap(),
-                tags: question.tags,
-            };
-            match store.update_question(question, id, account_id).await {
-                Ok(res) => {
-                    event!(target: "warp-rest-api", Level::INFO, "UPDATE Question");
-                    Ok(warp::reply::json(&res))
-                }
-                Err(e) => Err(warp::reject::custom(e)),
+    let expected_versions =
+        if_match.and_then(|header| etag::parse_if_match(&header).into_versions());
+    if !may_edit(&store, id, &account_id).await? {
+        return Err(warp::reject::custom(handle_errors::Error::Unauthorized));
+    }
+
+    // A replica which lags behind could let changed text skip the check
+    let current = store.get_question_by_id_primary(id).await?;
+    let moderated = moderation
+        .moderate_changed(question, &stored_texts(&current))
+        .await?;
+    let flagged = moderated.is_flagged();
+
+    // Checked again in the transaction of the write, which locks the question
+    // until it is changed. The content filter is asked outside of it, to not
+    // hold the lock while waiting for an external API.
+    let written = store
+        .transaction(|store| async move {
+            if !may_edit(&store, id, &account_id).await? {
+                return Err(handle_errors::Error::Unauthorized);
             }
-        } else {
-            Err(warp::reject::custom(
-                title.expect_err("Expected API call to have failed here"),
-            ))
+            store
+                .update_question(moderated.content, id, expected_versions, flagged)
+                .await
+        })
+        .await;
+
+    match written {
+        Ok(res) => {
+            event!(target: "warp-rest-api", Level::INFO, "UPDATE Question");
+            let tag = etag::from_version(res.version);
+            Ok(warp::reply::with_header(warp::reply::json(&res), ETAG, tag))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[instrument]
+pub async fn patch_question(
+    id: i32,
+    if_match: Option<String>,
+    session: Session,
+    store: Store,
+    moderation: Moderation,
+    patch: QuestionPatch,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let expected_versions =
+        if_match.and_then(|header| etag::parse_if_match(&header).into_versions());
+    if !may_edit(&store, id, &account_id).await? {
+        return Err(warp::reject::custom(handle_errors::Error::Unauthorized));
+    }
+
+    // A replica which lags behind could let changed text skip the check
+    let current = store.get_question_by_id_primary(id).await?;
+    let moderated = moderation
+        .moderate_changed(patch, &stored_texts(&current))
+        .await?;
+    let flagged = moderated.is_flagged();
+
+    // Checked again in the transaction of the write, which locks the question
+    // until it is changed. The content filter is asked outside of it, to not
+    // hold the lock while waiting for an external API.
+    let written = store
+        .transaction(|store| async move {
+            if !may_edit(&store, id, &account_id).await? {
+                return Err(handle_errors::Error::Unauthorized);
+            }
+            store
+                .patch_question(moderated.content, id, expected_versions, flagged)
+                .await
+        })
+        .await;
+
+    match written {
+        Ok(res) => {
+            event!(target: "warp-rest-api", Level::INFO, "PATCH Question");
+            let tag = etag::from_version(res.version);
+            Ok(warp::reply::with_header(warp::reply::json(&res), ETAG, tag))
         }
-    } else {
-        Err(warp::reject::custom(handle_errors::Error::Unauthorized))
+        Err(e) => Err(warp::reject::custom(e)),
     }
 }
 
+/// Authors can always edit their questions, other users only
+/// once their reputation grants them the privilege to.
+async fn may_edit(
+    store: &Store,
+    id: i32,
+    account_id: &AccountId,
+) -> Result<bool, handle_errors::Error> {
+    if store.is_question_owner(id, account_id).await? {
+        return Ok(true);
+    }
+
+    let reputation = store.get_reputation(account_id.0).await?;
+    Ok(Privilege::EditOthersPosts.is_granted(reputation))
+}
+
+/// Text which is already stored went through the profanity check
+/// before, so it is only checked again if it was changed.
+fn stored_texts(question: &Question) -> [(&'static str, &str); 2] {
+    [("title", &question.title), ("content", &question.content)]
+}
+
 #[instrument]
 pub async fn delete_question(
+    id: i32,
+    if_match: Option<String>,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let expected_versions =
+        if_match.and_then(|header| etag::parse_if_match(&header).into_versions());
+    let deleted = store
+        .transaction(|store| async move {
+            if !store.is_question_owner(id, &account_id).await? {
+                return Err(handle_errors::Error::Unauthorized);
+            }
+            store
+                .delete_question(id, account_id, expected_versions)
+                .await
+        })
+        .await;
+
+    match deleted {
+        Ok(true) => {
+            event!(target: "warp-rest-api", Level::INFO, "DELETE Question");
+            Ok(warp::reply::with_status(
+                format!("Question {id} deleted"),
+                StatusCode::OK,
+            ))
+        }
+        Ok(false) => Err(warp::reject::custom(handle_errors::Error::NotFound)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[instrument]
+pub async fn restore_question(
     id: i32,
     session: Session,
     store: Store,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
-    if store.is_question_owner(id, &account_id).await? {
-        match store.delete_question(id, account_id).await {
-            Ok(_) => {
-                event!(target: "warp-rest-api", Level::INFO, "DELETE Question");
-                Ok(warp::reply::with_status(
-                    format!("Question {} deleted", id),
-                    StatusCode::OK,
-                ))
+    let restored = store
+        .transaction(|store| async move {
+            if !store.is_question_owner(id, &account_id).await? {
+                return Err(handle_errors::Error::Unauthorized);
             }
-            Err(e) => Err(warp::reject::custom(e)),
+            store.restore_question(id, account_id).await
+        })
+        .await;
+
+    match restored {
+        Ok(res) => {
+            event!(target: "warp-rest-api", Level::INFO, "RESTORE Question");
+            Ok(warp::reply::json(&res))
         }
-    } else {
-        Err(warp::reject::custom(handle_errors::Error::Unauthorized))
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[cfg(test)]
+mod question_tests {
+    use crate::{
+        routes::{
+            authentication::verify_token,
+            test_helpers::{expired_token_for, moderator_token, test_routes, token},
+        },
+        store::memory::MemoryStore,
+        types::account::AccountId,
+    };
+    use std::sync::Arc;
+    use warp::http::StatusCode;
+
+    #[tokio::test]
+    async fn add_and_get_question() {
+        let store = Arc::new(MemoryStore::default());
+        let token = token(&store, "author@email.com");
+        let routes = test_routes(store);
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/questions")
+            .header("Authorization", &token)
+            .json(&serde_json::json!({"title": "Title", "content": "Content"}))
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = warp::test::request()
+            .path("/questions")
+            .reply(&routes)
+            .await;
+        let questions: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(questions[0]["title"], "Title");
+        assert_eq!(questions[0]["moderation_status"], "approved");
+    }
+
+    #[tokio::test]
+    async fn hide_flagged_question() {
+        let store = Arc::new(MemoryStore::default());
+        let token = token(&store, "author@email.com");
+        let routes = test_routes(store);
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/questions")
+            .header("Authorization", &token)
+            .json(&serde_json::json!({"title": "Title", "content": "damn"}))
+            .reply(&routes)
+            .await;
+        let question: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(question["moderation_status"], "pending");
+
+        let res = warp::test::request()
+            .path("/questions")
+            .reply(&routes)
+            .await;
+        assert_eq!(res.body().as_ref(), b"[]");
+    }
+
+    #[tokio::test]
+    async fn only_author_and_moderators_see_pending_question() {
+        let store = Arc::new(MemoryStore::default());
+        let author = token(&store, "author@email.com");
+        let other = token(&store, "other@email.com");
+        let moderator = moderator_token(&store, "moderator@email.com");
+        let routes = test_routes(store);
+
+        warp::test::request()
+            .method("POST")
+            .path("/questions")
+            .header("Authorization", &author)
+            .json(&serde_json::json!({"title": "Title", "content": "damn"}))
+            .reply(&routes)
+            .await;
+
+        let res = warp::test::request()
+            .path("/questions/1")
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let res = warp::test::request()
+            .path("/questions/1")
+            .header("Authorization", &other)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        for token in [&author, &moderator] {
+            let res = warp::test::request()
+                .path("/questions/1")
+                .header("Authorization", token)
+                .reply(&routes)
+                .await;
+            assert_eq!(res.status(), StatusCode::OK);
+            let question: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+            assert_eq!(question["moderation_status"], "pending");
+        }
+    }
+
+    #[tokio::test]
+    async fn stale_token_gets_public_view() {
+        let store = Arc::new(MemoryStore::default());
+        let author = token(&store, "author@email.com");
+        let routes = test_routes(store);
+
+        for content in ["Content", "damn"] {
+            warp::test::request()
+                .method("POST")
+                .path("/questions")
+                .header("Authorization", &author)
+                .json(&serde_json::json!({"title": "Title", "content": content}))
+                .reply(&routes)
+                .await;
+        }
+
+        let expired = expired_token_for(&AccountId(1));
+        assert!(verify_token(&expired).is_err());
+        for token in [expired.as_str(), "not a token"] {
+            let res = warp::test::request()
+                .path("/questions/1")
+                .header("Authorization", token)
+                .reply(&routes)
+                .await;
+            assert_eq!(res.status(), StatusCode::OK);
+
+            // The author's pending question is hidden, as for anyone anonymous
+            let res = warp::test::request()
+                .path("/questions/2")
+                .header("Authorization", token)
+                .reply(&routes)
+                .await;
+            assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        }
+    }
+
+    #[tokio::test]
+    async fn update_needs_current_version() {
+        let store = Arc::new(MemoryStore::default());
+        let token = token(&store, "author@email.com");
+        let routes = test_routes(store);
+
+        warp::test::request()
+            .method("POST")
+            .path("/questions")
+            .header("Authorization", &token)
+            .json(&serde_json::json!({"title": "Title", "content": "Content"}))
+            .reply(&routes)
+            .await;
+
+        let update = |etag: &'static str| {
+            warp::test::request()
+                .method("PUT")
+                .path("/questions/1")
+                .header("Authorization", &token)
+                .header("if-match", etag)
+                .json(&serde_json::json!({"title": "New title", "content": "Content"}))
+        };
+
+        let res = update("\"1\"").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["etag"], "\"2\"");
+
+        let res = update("\"1\"").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[tokio::test]
+    async fn current_etag_is_not_modified() {
+        let store = Arc::new(MemoryStore::default());
+        let token = token(&store, "author@email.com");
+        let routes = test_routes(store);
+
+        warp::test::request()
+            .method("POST")
+            .path("/questions")
+            .header("Authorization", &token)
+            .json(&serde_json::json!({"title": "Title", "content": "Content"}))
+            .reply(&routes)
+            .await;
+
+        let get = |etag: &'static str| {
+            warp::test::request()
+                .path("/questions/1")
+                .header("if-none-match", etag)
+        };
+
+        let res = get("\"1\"").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert!(res.body().is_empty());
+
+        // If-None-Match compares weakly
+        let res = get("W/\"1\"").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+
+        let res = get("\"0\"").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["etag"], "\"1\"");
+    }
+
+    #[tokio::test]
+    async fn weak_or_stale_if_match_fails() {
+        let store = Arc::new(MemoryStore::default());
+        let token = token(&store, "author@email.com");
+        let routes = test_routes(store);
+
+        warp::test::request()
+            .method("POST")
+            .path("/questions")
+            .header("Authorization", &token)
+            .json(&serde_json::json!({"title": "Title", "content": "Content"}))
+            .reply(&routes)
+            .await;
+
+        let request = |method: &str, etag: &'static str| {
+            warp::test::request()
+                .method(method)
+                .path("/questions/1")
+                .header("Authorization", &token)
+                .header("if-match", etag)
+        };
+
+        // If-Match compares strongly, so a weak tag of the current version fails
+        let res = request("PATCH", "W/\"1\"")
+            .json(&serde_json::json!({"title": "New title"}))
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+
+        let res = request("PUT", "W/\"1\"")
+            .json(&serde_json::json!({"title": "New title", "content": "Content"}))
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+
+        let res = request("DELETE", "\"2\"").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+        let res = request("DELETE", "W/\"1\"").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+
+        let res = request("DELETE", "\"1\"").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn patch_sets_keeps_and_clears_tags() {
+        let store = Arc::new(MemoryStore::default());
+        let token = token(&store, "author@email.com");
+        let routes = test_routes(store);
+
+        warp::test::request()
+            .method("POST")
+            .path("/questions")
+            .header("Authorization", &token)
+            .json(&serde_json::json!({"title": "Title", "content": "Content"}))
+            .reply(&routes)
+            .await;
+
+        let patch = |body: serde_json::Value| {
+            warp::test::request()
+                .method("PATCH")
+                .path("/questions/1")
+                .header("Authorization", &token)
+                .json(&body)
+        };
+
+        let res = patch(serde_json::json!({"tags": ["faq"]}))
+            .reply(&routes)
+            .await;
+        let question: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(question["tags"], serde_json::json!(["faq"]));
+
+        // An absent field is left as it is
+        let res = patch(serde_json::json!({"title": "New title"}))
+            .reply(&routes)
+            .await;
+        let question: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(question["title"], "New title");
+        assert_eq!(question["tags"], serde_json::json!(["faq"]));
+
+        // An explicit null removes the tags
+        let res = patch(serde_json::json!({"tags": null}))
+            .reply(&routes)
+            .await;
+        let question: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(question["tags"], serde_json::Value::Null);
+        assert_eq!(question["title"], "New title");
+    }
+
+    #[tokio::test]
+    async fn only_author_deletes_question() {
+        let store = Arc::new(MemoryStore::default());
+        let author = token(&store, "author@email.com");
+        let other = token(&store, "other@email.com");
+        let routes = test_routes(store);
+
+        warp::test::request()
+            .method("POST")
+            .path("/questions")
+            .header("Authorization", &author)
+            .json(&serde_json::json!({"title": "Title", "content": "Content"}))
+            .reply(&routes)
+            .await;
+
+        let delete = |token: &str| {
+            warp::test::request()
+                .method("DELETE")
+                .path("/questions/1")
+                .header("Authorization", token)
+        };
+
+        let res = delete(&other).reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = delete(&author).reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = warp::test::request()
+            .path("/questions/1")
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn only_author_restores_question() {
+        let store = Arc::new(MemoryStore::default());
+        let author = token(&store, "author@email.com");
+        let other = token(&store, "other@email.com");
+        let routes = test_routes(store);
+
+        warp::test::request()
+            .method("POST")
+            .path("/questions")
+            .header("Authorization", &author)
+            .json(&serde_json::json!({"title": "Title", "content": "Content"}))
+            .reply(&routes)
+            .await;
+        warp::test::request()
+            .method("DELETE")
+            .path("/questions/1")
+            .header("Authorization", &author)
+            .reply(&routes)
+            .await;
+
+        let restore = |token: &str| {
+            warp::test::request()
+                .method("POST")
+                .path("/questions/1/restore")
+                .header("Authorization", token)
+        };
+
+        let res = restore(&other).reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = restore(&author).reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let question: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(question["title"], "Title");
+
+        let res = warp::test::request()
+            .path("/questions/1")
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // Only deleted questions can be restored
+        let res = restore(&author).reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
     }
 }
@@ -0,0 +1,171 @@
+use tracing::{event, instrument, Level};
+use warp::http::StatusCode;
+
+use crate::{
+    store::Store,
+    types::{
+        account::Session,
+        report::{NewReport, ReportTarget, MAX_NOTE_LENGTH},
+    },
+};
+
+#[instrument]
+pub async fn report_question(
+    id: i32,
+    session: Session,
+    store: Store,
+    report: NewReport,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    add_report(ReportTarget::Question(id), session, store, report).await
+}
+
+#[instrument]
+pub async fn report_answer(
+    id: i32,
+    session: Session,
+    store: Store,
+    report: NewReport,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    add_report(ReportTarget::Answer(id), session, store, report).await
+}
+
+async fn add_report(
+    target: ReportTarget,
+    session: Session,
+    store: Store,
+    report: NewReport,
+) -> Result<warp::reply::WithStatus<&'static str>, warp::Rejection> {
+    if report
+        .note
+        .as_ref()
+        .is_some_and(|note| note.chars().count() > MAX_NOTE_LENGTH)
+    {
+        return Err(warp::reject::custom(handle_errors::Error::InvalidInput(
+            format!("note can have at most {MAX_NOTE_LENGTH} characters"),
+        )));
+    }
+
+    match store.add_report(target, session.account_id, report).await {
+        Ok(true) => {
+            event!(target: "warp-rest-api", Level::INFO, "POST NEW Report");
+            Ok(warp::reply::with_status(
+                "Report added",
+                StatusCode::CREATED,
+            ))
+        }
+        // Reporting the same post twice changes nothing
+        Ok(false) => Ok(warp::reply::with_status("Already reported", StatusCode::OK)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[cfg(test)]
+mod report_tests {
+    use crate::{
+        routes::test_helpers::{test_routes, token},
+        store::memory::MemoryStore,
+        types::report::ReportThreshold,
+    };
+    use std::sync::Arc;
+    use warp::http::StatusCode;
+
+    #[tokio::test]
+    async fn hide_question_at_threshold() {
+        let store = Arc::new(MemoryStore::default().with_report_threshold(ReportThreshold(2)));
+        let tokens: Vec<String> = ["author", "first", "second"]
+            .iter()
+            .map(|name| token(&store, &format!("{name}@email.com")))
+            .collect();
+        let routes = test_routes(store);
+
+        warp::test::request()
+            .method("POST")
+            .path("/questions")
+            .header("Authorization", &tokens[0])
+            .json(&serde_json::json!({"title": "Title", "content": "Content"}))
+            .reply(&routes)
+            .await;
+
+        let report = |token: &str| {
+            warp::test::request()
+                .method("POST")
+                .path("/questions/1/report")
+                .header("Authorization", token)
+                .json(&serde_json::json!({"reason": "spam"}))
+        };
+
+        // Reporting twice counts once
+        let res = report(&tokens[1]).reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let res = report(&tokens[1]).reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = warp::test::request()
+            .path("/questions/1")
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = report(&tokens[2]).reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+
+        let res = warp::test::request()
+            .path("/questions")
+            .reply(&routes)
+            .await;
+        assert_eq!(res.body().as_ref(), b"[]");
+        let res = warp::test::request()
+            .path("/questions/1")
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        // The author still sees it, waiting for a moderator
+        let res = warp::test::request()
+            .path("/questions/1")
+            .header("Authorization", &tokens[0])
+            .reply(&routes)
+            .await;
+        let question: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(question["moderation_status"], "pending");
+    }
+
+    #[tokio::test]
+    async fn authors_cannot_report_own_posts() {
+        let store = Arc::new(MemoryStore::default().with_report_threshold(ReportThreshold(1)));
+        let author = token(&store, "author@email.com");
+        let routes = test_routes(store);
+
+        warp::test::request()
+            .method("POST")
+            .path("/questions")
+            .header("Authorization", &author)
+            .json(&serde_json::json!({"title": "Title", "content": "Content"}))
+            .reply(&routes)
+            .await;
+        warp::test::request()
+            .method("POST")
+            .path("/answers")
+            .header("Authorization", &author)
+            .json(&serde_json::json!({"content": "Answer", "questionId": "1"}))
+            .reply(&routes)
+            .await;
+
+        for path in ["/questions/1/report", "/answers/1/report"] {
+            let res = warp::test::request()
+                .method("POST")
+                .path(path)
+                .header("Authorization", &author)
+                .json(&serde_json::json!({"reason": "spam"}))
+                .reply(&routes)
+                .await;
+            assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        }
+
+        let res = warp::test::request()
+            .path("/questions/1")
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,69 @@
+use chrono::{Duration, Utc};
+use std::{env, sync::Arc};
+use warp::{Filter, Reply};
+
+use crate::{
+    build_routes,
+    config::ModerationPolicy,
+    profanity::{Moderation, WordlistFilter},
+    routes::authentication::issue_token,
+    store::{memory::MemoryStore, Store},
+    types::account::AccountId,
+};
+
+/// Flags every `damn`, so posts can be sent to the moderation queue
+pub fn moderation() -> Moderation {
+    Moderation::new(
+        Arc::new(WordlistFilter::new(["damn"])),
+        ModerationPolicy::Flag,
+    )
+}
+
+/// All routes on top of `store`, moderated by `moderation`
+pub fn test_routes(store: Arc<MemoryStore>) -> impl Filter<Extract = impl Reply> + Clone {
+    build_routes(Store::new(store), moderation())
+}
+
+/// Tokens can only be issued and verified with a key
+pub fn set_paseto_key() {
+    env::set_var("PASETO_KEY", "RANDOM WORDS WINTER MACINTOSH PC");
+}
+
+/// Creates an account and returns a token to log in with
+pub fn token(store: &MemoryStore, email: &str) -> String {
+    token_for(&store.create_account(email))
+}
+
+/// Creates a moderator and returns a token to log in with
+pub fn moderator_token(store: &MemoryStore, email: &str) -> String {
+    let account_id = store.create_account(email);
+    store.set_moderator(&account_id);
+    token_for(&account_id)
+}
+
+/// Creates an admin and returns a token to log in with
+pub fn admin_token(store: &MemoryStore, email: &str) -> String {
+    let account_id = store.create_account(email);
+    store.set_admin(&account_id);
+    token_for(&account_id)
+}
+
+pub fn token_for(account_id: &AccountId) -> String {
+    set_paseto_key();
+    issue_token(account_id)
+}
+
+/// A token which expired a day ago
+pub fn expired_token_for(account_id: &AccountId) -> String {
+    set_paseto_key();
+    let key = env::var("PASETO_KEY").unwrap();
+    let now = Utc::now();
+
+    paseto::tokens::PasetoBuilder::new()
+        .set_encryption_key(&Vec::from(key.as_bytes()))
+        .set_expiration(&(now - Duration::days(1)))
+        .set_not_before(&(now - Duration::days(2)))
+        .set_claim("account_id", serde_json::json!(account_id))
+        .build()
+        .unwrap()
+}
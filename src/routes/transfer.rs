@@ -0,0 +1,254 @@
+use futures::{
+    io::{AsyncBufReadExt, Cursor},
+    stream::{Stream, StreamExt, TryStreamExt},
+};
+use std::{io, time::Duration};
+use tracing::{event, instrument, Level};
+use warp::hyper::body::{Body, Buf};
+
+use crate::{
+    store::Store,
+    transfer,
+    types::account::{AccountId, Session},
+};
+
+/// Never includes password hashes, only the `export` command does
+#[instrument]
+pub async fn export(session: Session, store: Store) -> Result<impl warp::Reply, warp::Rejection> {
+    ensure_admin(&store, &session.account_id).await?;
+    event!(target: "warp-rest-api", Level::INFO, "EXPORT Records");
+
+    // The status is sent with the first line, so a failure later on can
+    // only end the response early
+    let lines = transfer::export_lines(&store, false).map_err(|e| {
+        event!(target: "warp-rest-api", Level::ERROR, "Export aborted: {}", e);
+        io::Error::other(e.to_string())
+    });
+
+    Ok(warp::reply::with_header(
+        warp::reply::Response::new(Body::wrap_stream(lines)),
+        "content-type",
+        "application/x-ndjson",
+    ))
+}
+
+/// Largest body `POST /admin/import` reads, larger exports have to be
+/// imported with the `import` command
+pub const MAX_IMPORT_BYTES: usize = 64 * 1024 * 1024;
+
+/// How long `POST /admin/import` waits for the next part of the body
+const IMPORT_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Refuses accounts which can log in or moderate, only the `import`
+/// command stores those. The body is read completely before the
+/// import begins, so a slow client cannot keep a transaction open.
+#[instrument(skip(body))]
+pub async fn import<S, B>(
+    session: Session,
+    store: Store,
+    body: S,
+) -> Result<impl warp::Reply, warp::Rejection>
+where
+    S: Stream<Item = Result<B, warp::Error>> + Send + Unpin + 'static,
+    B: Buf + Send,
+{
+    ensure_admin(&store, &session.account_id).await?;
+
+    let body = read_body(body, MAX_IMPORT_BYTES, IMPORT_READ_TIMEOUT).await?;
+    let lines = Cursor::new(body).lines();
+
+    match transfer::import(&store, lines, false).await {
+        Ok(imported) => {
+            event!(
+                target: "warp-rest-api",
+                Level::INFO,
+                accounts = imported.accounts,
+                questions = imported.questions,
+                answers = imported.answers,
+                "IMPORT Records"
+            );
+            Ok(warp::reply::json(&imported))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/// Reads the body into memory, giving up when it grows beyond `limit`
+/// bytes or no part of it arrives for `timeout`
+async fn read_body<S, B>(
+    mut body: S,
+    limit: usize,
+    timeout: Duration,
+) -> Result<Vec<u8>, handle_errors::Error>
+where
+    S: Stream<Item = Result<B, warp::Error>> + Unpin,
+    B: Buf,
+{
+    let mut buffer = Vec::new();
+    loop {
+        let mut chunk = match tokio::time::timeout(timeout, body.next()).await {
+            Ok(Some(chunk)) => chunk.map_err(|e| {
+                handle_errors::Error::TransferError(io::Error::other(e.to_string()))
+            })?,
+            Ok(None) => return Ok(buffer),
+            Err(_) => return Err(handle_errors::Error::BodyTimeout),
+        };
+        if buffer.len() + chunk.remaining() > limit {
+            return Err(handle_errors::Error::BodyTooLarge(limit));
+        }
+        buffer.extend_from_slice(&chunk.copy_to_bytes(chunk.remaining()));
+    }
+}
+
+/// Exports contain the email of every account, which moderators must
+/// not see
+async fn ensure_admin(store: &Store, account_id: &AccountId) -> Result<(), handle_errors::Error> {
+    if store.is_admin(account_id).await? {
+        Ok(())
+    } else {
+        Err(handle_errors::Error::Unauthorized)
+    }
+}
+
+#[cfg(test)]
+mod transfer_route_tests {
+    use super::read_body;
+    use crate::{
+        routes::test_helpers::{admin_token, moderator_token, test_routes, token},
+        store::memory::MemoryStore,
+    };
+    use futures::{stream, StreamExt};
+    use std::{sync::Arc, time::Duration};
+    use warp::{http::StatusCode, hyper::body::Bytes};
+
+    #[tokio::test]
+    async fn export_into_empty_store() {
+        let source = Arc::new(MemoryStore::default());
+        let admin = admin_token(&source, "admin@email.com");
+        let moderator = moderator_token(&source, "moderator@email.com");
+        let author = token(&source, "author@email.com");
+        let routes = test_routes(source);
+
+        warp::test::request()
+            .method("POST")
+            .path("/questions")
+            .header("Authorization", &author)
+            .json(&serde_json::json!({"title": "Title", "content": "Content"}))
+            .reply(&routes)
+            .await;
+
+        // Moderators must not see the emails of all accounts
+        for token in [&author, &moderator] {
+            let res = warp::test::request()
+                .path("/admin/export")
+                .header("Authorization", token)
+                .reply(&routes)
+                .await;
+            assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        // Password hashes are never sent, even when asked for
+        let res = warp::test::request()
+            .path("/admin/export?passwords=true")
+            .header("Authorization", &admin)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["content-type"], "application/x-ndjson");
+        let export = res.body().clone();
+        let text = std::str::from_utf8(&export).unwrap();
+        assert_eq!(text.lines().count(), 4);
+        assert!(!text.contains(r#""password""#));
+
+        // The target needs an admin of its own to accept the import
+        let target = Arc::new(MemoryStore::default());
+        let admin = admin_token(&target, "admin@email.com");
+        let moderator = moderator_token(&target, "moderator@email.com");
+        let routes = test_routes(target);
+        let lines = |prefix: &str| -> Vec<u8> {
+            export
+                .split(|byte| *byte == b'\n')
+                .filter(|line| line.starts_with(prefix.as_bytes()))
+                .collect::<Vec<_>>()
+                .join(&b'\n')
+        };
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/admin/import")
+            .header("Authorization", &moderator)
+            .body(export.clone())
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        // The admin and the moderator of the export must not become ones
+        // of the target
+        let res = warp::test::request()
+            .method("POST")
+            .path("/admin/import")
+            .header("Authorization", &admin)
+            .body(export.clone())
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        // The author of the question is not part of the import
+        let res = warp::test::request()
+            .method("POST")
+            .path("/admin/import")
+            .header("Authorization", &admin)
+            .body(lines(r#"{"type":"question""#))
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        // The author got an id the target has not taken yet
+        let author_and_question = [
+            lines(r#"{"type":"account","id":3,"#),
+            lines(r#"{"type":"question""#),
+        ]
+        .join(&b'\n');
+        let res = warp::test::request()
+            .method("POST")
+            .path("/admin/import")
+            .header("Authorization", &admin)
+            .body(author_and_question)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let imported: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(
+            imported,
+            serde_json::json!({"accounts": 1, "questions": 1, "answers": 0})
+        );
+
+        let res = warp::test::request()
+            .path("/questions/1")
+            .reply(&routes)
+            .await;
+        let question: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(question["title"], "Title");
+    }
+
+    #[tokio::test]
+    async fn read_body_within_limits() {
+        let chunks = || stream::iter(["first\n", "second\n"].map(|chunk| Ok(Bytes::from(chunk))));
+        let timeout = Duration::from_millis(50);
+
+        let body = read_body(chunks(), 13, timeout).await.unwrap();
+        assert_eq!(body, b"first\nsecond\n");
+
+        assert!(matches!(
+            read_body(chunks(), 12, timeout).await,
+            Err(handle_errors::Error::BodyTooLarge(12))
+        ));
+
+        // A client which stops sending is not waited for
+        let stalled = chunks().chain(stream::pending());
+        assert!(matches!(
+            read_body(stalled, 1024, timeout).await,
+            Err(handle_errors::Error::BodyTimeout)
+        ));
+    }
+}
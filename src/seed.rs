@@ -0,0 +1,270 @@
+use serde::Deserialize;
+use std::{fs, path::Path};
+
+use handle_errors::Error;
+
+use crate::routes::authentication::hash_password;
+use crate::store::Store;
+use crate::types::{
+    account::{Account, AccountId, ProfileUpdate},
+    answer::Answer,
+    question::NewQuestion,
+};
+
+/// Accounts, questions and answers to load into the store
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Fixture {
+    #[serde(default)]
+    pub accounts: Vec<FixtureAccount>,
+    #[serde(default)]
+    pub questions: Vec<FixtureQuestion>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct FixtureAccount {
+    pub email: String,
+    /// In plain text, it is hashed like on registration
+    pub password: String,
+    /// Profile of the account, only set when the fixture creates it
+    pub profile: Option<ProfileUpdate>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct FixtureQuestion {
+    /// Email of the author, who is either in the fixture or in the store
+    pub author: String,
+    pub title: String,
+    pub content: String,
+    pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub answers: Vec<FixtureAnswer>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct FixtureAnswer {
+    /// Email of the author, who is either in the fixture or in the store
+    pub author: String,
+    pub content: String,
+}
+
+/// What a run added, which is nothing once the fixture was loaded
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Seeded {
+    pub accounts: usize,
+    pub questions: usize,
+    pub answers: usize,
+}
+
+impl Fixture {
+    /// Reads a `.json` file as JSON and every other file as YAML
+    ///
+    /// # Errors
+    ///
+    /// With `FixtureError` if the file cannot be read or parsed
+    pub fn from_file(path: &Path) -> Result<Fixture, Error> {
+        let fixture_error = |e: String| Error::FixtureError(format!("{}: {e}", path.display()));
+
+        let text = fs::read_to_string(path).map_err(|e| fixture_error(e.to_string()))?;
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            serde_json::from_str(&text).map_err(|e| fixture_error(e.to_string()))
+        } else {
+            serde_yaml::from_str(&text).map_err(|e| fixture_error(e.to_string()))
+        }
+    }
+}
+
+/// Adds what the store is missing of the fixture, all of it or nothing.
+/// Accounts are matched by email, questions by author and title, and
+/// answers by question, author and content. Rows which exist are left
+/// as they are, profiles included, so loading a fixture again changes
+/// nothing. Posts are stored approved, without asking the content filter.
+///
+/// # Errors
+///
+/// With `FixtureError` if an author is missing, or with the error of
+/// the store if a row cannot be stored
+pub async fn seed(store: &Store, fixture: Fixture) -> Result<Seeded, Error> {
+    store
+        .transaction(|store| async move {
+            let mut seeded = Seeded::default();
+
+            for account in fixture.accounts {
+                match store.get_account(account.email.clone()).await {
+                    Ok(_) => {}
+                    Err(Error::NotFound) => {
+                        store
+                            .add_account(Account {
+                                id: None,
+                                email: account.email.clone(),
+                                password: hash_password(account.password.as_bytes()),
+                            })
+                            .await?;
+                        seeded.accounts += 1;
+
+                        if let Some(profile) = account.profile {
+                            let id = author_id(&store, &account.email).await?;
+                            store.update_profile(id, profile).await?;
+                        }
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+
+            for question in fixture.questions {
+                let author = author_id(&store, &question.author).await?;
+                let stored =
+                    if let Some(stored) = store.find_question(&author, &question.title).await? {
+                        stored
+                    } else {
+                        seeded.questions += 1;
+                        let new_question = NewQuestion {
+                            title: question.title,
+                            content: question.content,
+                            tags: question.tags,
+                        };
+                        store.add_question(new_question, author, false).await?
+                    };
+
+                for answer in question.answers {
+                    let author = author_id(&store, &answer.author).await?;
+                    if store
+                        .answer_exists(stored.id.0, &author, &answer.content)
+                        .await?
+                    {
+                        continue;
+                    }
+
+                    let new_answer = Answer {
+                        content: answer.content,
+                        question_id: stored.id.0,
+                    };
+                    store.add_answer(new_answer, author, false).await?;
+                    seeded.answers += 1;
+                }
+            }
+
+            Ok(seeded)
+        })
+        .await
+}
+
+async fn author_id(store: &Store, email: &str) -> Result<AccountId, Error> {
+    match store.get_account(email.to_string()).await {
+        Ok(credentials) => Ok(credentials.id),
+        Err(Error::NotFound) => Err(Error::FixtureError(format!(
+            "there is no account with the email {email}"
+        ))),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod seed_tests {
+    use super::{seed, Fixture, Seeded};
+    use crate::store::{memory::MemoryStore, Store};
+    use crate::types::account::ProfileUpdate;
+    use handle_errors::Error;
+    use std::sync::Arc;
+
+    const FIXTURE: &str = "
+accounts:
+  - email: alice@email.com
+    password: secret
+    profile:
+      display_name: Alice
+  - email: bob@email.com
+    password: secret
+questions:
+  - author: alice@email.com
+    title: How to seed?
+    content: With a fixture
+    tags: [seed, fixture]
+    answers:
+      - author: bob@email.com
+        content: Run it twice
+";
+
+    #[tokio::test]
+    async fn seed_twice_adds_once() {
+        let store = Store::new(Arc::new(MemoryStore::default()));
+
+        let fixture: Fixture = serde_yaml::from_str(FIXTURE).unwrap();
+        let seeded = seed(&store, fixture).await.unwrap();
+        assert_eq!(
+            seeded,
+            Seeded {
+                accounts: 2,
+                questions: 1,
+                answers: 1
+            }
+        );
+
+        let questions = store.get_questions(None, 0).await.unwrap();
+        assert_eq!(questions[0].author.display_name.as_deref(), Some("Alice"));
+
+        // A profile edited since is kept
+        let alice = store
+            .get_account("alice@email.com".to_string())
+            .await
+            .unwrap();
+        let edited = ProfileUpdate {
+            display_name: Some("Alice Edited".to_string()),
+            bio: None,
+            avatar_url: None,
+        };
+        store.update_profile(alice.id, edited).await.unwrap();
+
+        let fixture: Fixture = serde_yaml::from_str(FIXTURE).unwrap();
+        assert_eq!(seed(&store, fixture).await.unwrap(), Seeded::default());
+
+        let questions = store.get_questions(None, 0).await.unwrap();
+        assert_eq!(questions.len(), 1);
+        assert_eq!(
+            questions[0].author.display_name.as_deref(),
+            Some("Alice Edited")
+        );
+        assert_eq!(
+            questions[0].tags,
+            Some(vec!["seed".to_string(), "fixture".to_string()])
+        );
+        // Hashed like on registration
+        let account = store
+            .get_account("bob@email.com".to_string())
+            .await
+            .unwrap();
+        assert!(argon2::verify_encoded(&account.password.unwrap(), b"secret").unwrap());
+    }
+
+    #[tokio::test]
+    async fn unknown_author_adds_nothing() {
+        let store = Store::new(Arc::new(MemoryStore::default()));
+        let fixture: Fixture = serde_yaml::from_str(
+            "
+accounts:
+  - email: alice@email.com
+    password: secret
+questions:
+  - author: nobody@email.com
+    title: Title
+    content: Content
+",
+        )
+        .unwrap();
+
+        assert!(matches!(
+            seed(&store, fixture).await,
+            Err(Error::FixtureError(_))
+        ));
+        assert!(matches!(
+            store.get_account("alice@email.com".to_string()).await,
+            Err(Error::NotFound)
+        ));
+    }
+}
@@ -0,0 +1,1148 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use futures::stream::{self, BoxStream, StreamExt};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use handle_errors::Error;
+
+use super::{
+    AccountRepository, ModerationRepository, QuestionRepository, Repository, TransferRepository,
+    UnitOfWork,
+};
+use crate::reputation::{Contributions, ReputationRules};
+use crate::types::{
+    account::{Account, AccountId, Author, Credentials, Profile, ProfileUpdate},
+    answer::{Answer, AnswerId, PostedAnswer},
+    export::{AccountRecord, AnswerRecord, QuestionRecord, Record},
+    moderation::{ModeratedPosts, ModerationStatus},
+    question::{NewQuestion, Question, QuestionId, QuestionPatch},
+    report::{NewReport, Report, ReportAction, ReportId, ReportTarget, ReportThreshold},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+struct StoredAccount {
+    account: Credentials,
+    profile: ProfileUpdate,
+    joined_on: DateTime<Utc>,
+    is_moderator: bool,
+    is_admin: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct StoredQuestion {
+    question: Question,
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct StoredAnswer {
+    answer: PostedAnswer,
+    account_id: AccountId,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Tables {
+    accounts: Vec<StoredAccount>,
+    questions: Vec<StoredQuestion>,
+    answers: Vec<StoredAnswer>,
+    reports: Vec<Report>,
+    /// Last id of every table, like the sequences of `serial` columns.
+    /// Copies of the tables share them, so units of work never hand
+    /// out the same id and rolling back does not give ids back.
+    last_ids: Arc<Mutex<[i32; 4]>>,
+}
+
+/// Emails are unique and looked up regardless of case, like the
+/// `lower(email)` index
+fn same_email(a: &str, b: &str) -> bool {
+    a.to_lowercase() == b.to_lowercase()
+}
+
+#[derive(Clone, Copy)]
+enum Table {
+    Accounts,
+    Questions,
+    Answers,
+    Reports,
+}
+
+impl Tables {
+    fn next_id(&mut self, table: Table) -> i32 {
+        let mut last_ids = self.last_ids.lock().expect("memory store lock poisoned");
+        let last_id = &mut last_ids[table as usize];
+        *last_id += 1;
+        *last_id
+    }
+
+    /// Applies what a unit of work changed between `base` and `work`,
+    /// row by row, so rows written outside of it in the meantime stay
+    fn merge(&mut self, base: &Tables, work: &Tables) {
+        merge_rows(
+            &mut self.accounts,
+            &base.accounts,
+            &work.accounts,
+            |stored| stored.account.id.clone(),
+        );
+        merge_rows(
+            &mut self.questions,
+            &base.questions,
+            &work.questions,
+            |stored| stored.question.id.clone(),
+        );
+        merge_rows(&mut self.answers, &base.answers, &work.answers, |stored| {
+            stored.answer.id.clone()
+        });
+        merge_rows(&mut self.reports, &base.reports, &work.reports, |report| {
+            report.id.clone()
+        });
+    }
+
+    /// Like the foreign keys on `accounts`, only existing accounts can
+    /// be referenced
+    fn check_account(&self, account_id: &AccountId, constraint: &str) -> Result<(), Error> {
+        match self.account(account_id.0) {
+            Some(_) => Ok(()),
+            None => Err(Error::ForeignKeyViolation(Some(constraint.to_string()))),
+        }
+    }
+
+    fn account(&self, account_id: i32) -> Option<&StoredAccount> {
+        self.accounts
+            .iter()
+            .find(|stored| stored.account.id.0 == account_id)
+    }
+
+    /// Questions are shown with the current display name of their author
+    fn question(&self, stored: &StoredQuestion) -> Question {
+        let mut question = stored.question.clone();
+        question.author.display_name = self
+            .account(question.author.id.0)
+            .and_then(|account| account.profile.display_name.clone());
+        question
+    }
+
+    fn live_question(&self, id: i32) -> Option<&StoredQuestion> {
+        self.questions
+            .iter()
+            .find(|stored| stored.question.id.0 == id && stored.deleted_at.is_none())
+    }
+
+    fn live_question_mut(&mut self, id: i32) -> Option<&mut StoredQuestion> {
+        self.questions
+            .iter_mut()
+            .find(|stored| stored.question.id.0 == id && stored.deleted_at.is_none())
+    }
+
+    /// Same as `PgStore::conditional_write_error`
+    fn conditional_write_error(&self, id: i32) -> Error {
+        match self.live_question(id) {
+            Some(_) => Error::PreconditionFailed,
+            None => Error::NotFound,
+        }
+    }
+
+    fn on_live_question(&self, answer: &PostedAnswer) -> bool {
+        self.live_question(answer.question_id.0).is_some()
+    }
+
+    fn contributions(&self, account_id: &AccountId) -> Contributions {
+        let approved = |status: ModerationStatus| status == ModerationStatus::Approved;
+        let answers = self
+            .answers
+            .iter()
+            .filter(|stored| {
+                &stored.account_id == account_id && approved(stored.answer.moderation_status)
+            })
+            .map(|stored| &stored.answer);
+
+        Contributions {
+            questions_asked: count(self.questions.iter().filter(|stored| {
+                &stored.question.author.id == account_id
+                    && stored.deleted_at.is_none()
+                    && approved(stored.question.moderation_status)
+            })),
+            answers_given: count(answers.clone()),
+            answers_on_live_questions: count(answers.filter(|answer| {
+                self.live_question(answer.question_id.0)
+                    .is_some_and(|stored| approved(stored.question.moderation_status))
+            })),
+        }
+    }
+
+    fn set_status(&mut self, target: ReportTarget, status: ModerationStatus) {
+        match target {
+            ReportTarget::Question(id) => {
+                if let Some(stored) = self
+                    .questions
+                    .iter_mut()
+                    .find(|stored| stored.question.id.0 == id)
+                {
+                    stored.question.moderation_status = status;
+                }
+            }
+            ReportTarget::Answer(id) => {
+                if let Some(stored) = self
+                    .answers
+                    .iter_mut()
+                    .find(|stored| stored.answer.id.0 == id)
+                {
+                    stored.answer.moderation_status = status;
+                }
+            }
+        }
+    }
+
+    /// Marks the open reports of the post as resolved and returns them
+    fn resolve_reports(&mut self, target: ReportTarget, action: ReportAction) -> Vec<Report> {
+        let now = Utc::now();
+        self.reports
+            .iter_mut()
+            .filter(|report| reported(report, target) && report.resolved_on.is_none())
+            .map(|report| {
+                report.resolved_on = Some(now);
+                report.resolution = Some(action);
+                report.clone()
+            })
+            .collect()
+    }
+
+    /// The status and the author of a post which is not deleted
+    fn post(&self, target: ReportTarget) -> Option<(ModerationStatus, AccountId)> {
+        match target {
+            ReportTarget::Question(id) => self.live_question(id).map(|stored| {
+                (
+                    stored.question.moderation_status,
+                    stored.question.author.id.clone(),
+                )
+            }),
+            ReportTarget::Answer(id) => self
+                .answers
+                .iter()
+                .find(|stored| stored.answer.id.0 == id && self.on_live_question(&stored.answer))
+                .map(|stored| (stored.answer.moderation_status, stored.account_id.clone())),
+        }
+    }
+}
+
+/// Deletes the rows which are in `base` but not in `work`, and writes
+/// the rows of `work` which are new or differ from `base`
+fn merge_rows<T, K>(rows: &mut Vec<T>, base: &[T], work: &[T], key: impl Fn(&T) -> K)
+where
+    T: Clone + PartialEq,
+    K: PartialEq,
+{
+    rows.retain(|row| {
+        let id = key(row);
+        !base.iter().any(|before| key(before) == id) || work.iter().any(|after| key(after) == id)
+    });
+
+    for after in work {
+        let id = key(after);
+        if base.iter().any(|before| before == after) {
+            continue;
+        }
+        match rows.iter_mut().find(|row| key(row) == id) {
+            Some(row) => *row = after.clone(),
+            None => rows.push(after.clone()),
+        }
+    }
+}
+
+fn count<T>(iter: impl Iterator<Item = T>) -> i64 {
+    i64::try_from(iter.count()).unwrap_or(i64::MAX)
+}
+
+fn reported(report: &Report, target: ReportTarget) -> bool {
+    match target {
+        ReportTarget::Question(id) => report.question_id == Some(QuestionId(id)),
+        ReportTarget::Answer(id) => report.answer_id == Some(AnswerId(id)),
+    }
+}
+
+fn matches_version(question: &Question, expected_versions: Option<&Vec<i32>>) -> bool {
+    expected_versions.is_none_or(|versions| versions.contains(&question.version))
+}
+
+/// Keeps everything in memory, so handlers can be tested without
+/// a database. Mirrors the behaviour of `PgStore`.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    tables: Arc<Mutex<Tables>>,
+    reputation_rules: ReputationRules,
+    report_threshold: ReportThreshold,
+    /// A unit of work works on a copy of the tables. It keeps the
+    /// tables it was started from and how they looked at that time, to
+    /// apply its own changes to them when it is committed.
+    started_from: Mutex<Option<(Arc<Mutex<Tables>>, Tables)>>,
+}
+
+impl MemoryStore {
+    fn tables(&self) -> MutexGuard<'_, Tables> {
+        self.tables.lock().expect("memory store lock poisoned")
+    }
+
+    /// Conditional write of a question, like the `UPDATE` of `PgStore`
+    fn write_question(
+        &self,
+        id: i32,
+        expected_versions: Option<&Vec<i32>>,
+        flagged: bool,
+        change: impl FnOnce(&mut Question),
+    ) -> Result<Question, Error> {
+        let mut tables = self.tables();
+        let Some(stored) = tables
+            .live_question_mut(id)
+            .filter(|stored| matches_version(&stored.question, expected_versions))
+        else {
+            return Err(tables.conditional_write_error(id));
+        };
+
+        let question = &mut stored.question;
+        change(question);
+        question.version += 1;
+        question.updated_on = Utc::now();
+        if flagged {
+            question.moderation_status = ModerationStatus::Pending;
+        }
+
+        let stored = tables.live_question(id).expect("question was just updated");
+        Ok(tables.question(stored))
+    }
+
+    /// The tables the unit of work was started from and their copy,
+    /// `None` if it is finished already or the store is no unit of work
+    fn finish(&self) -> Option<(Arc<Mutex<Tables>>, Tables)> {
+        self.started_from
+            .lock()
+            .expect("memory store lock poisoned")
+            .take()
+    }
+
+    /// Adds an account without going through the registration
+    pub fn create_account(&self, email: &str) -> AccountId {
+        let mut tables = self.tables();
+        let id = AccountId(tables.next_id(Table::Accounts));
+        tables.accounts.push(StoredAccount {
+            account: Credentials {
+                id: id.clone(),
+                email: email.to_string(),
+                password: None,
+            },
+            profile: ProfileUpdate::default(),
+            joined_on: Utc::now(),
+            is_moderator: false,
+            is_admin: false,
+        });
+        id
+    }
+
+    /// Like `PgStore::with_report_threshold`
+    #[must_use]
+    pub fn with_report_threshold(mut self, report_threshold: ReportThreshold) -> Self {
+        self.report_threshold = report_threshold;
+        self
+    }
+
+    /// Moderators are only set up by hand, there is no API for it
+    pub fn set_moderator(&self, account_id: &AccountId) {
+        if let Some(stored) = self
+            .tables()
+            .accounts
+            .iter_mut()
+            .find(|stored| &stored.account.id == account_id)
+        {
+            stored.is_moderator = true;
+        }
+    }
+
+    /// Like moderators, admins are only set up by hand
+    pub fn set_admin(&self, account_id: &AccountId) {
+        if let Some(stored) = self
+            .tables()
+            .accounts
+            .iter_mut()
+            .find(|stored| &stored.account.id == account_id)
+        {
+            stored.is_admin = true;
+        }
+    }
+}
+
+/// A commit applies only the rows its unit of work inserted, changed
+/// or deleted, so writes to other rows made in the meantime are kept,
+/// like with `PgStore`. Where Postgres would make a second writer of
+/// the same row wait, the last commit wins here.
+#[async_trait]
+impl Repository for MemoryStore {
+    async fn begin(&self) -> Result<Arc<dyn UnitOfWork>, Error> {
+        let base = self.tables().clone();
+        Ok(Arc::new(MemoryStore {
+            tables: Arc::new(Mutex::new(base.clone())),
+            reputation_rules: self.reputation_rules.clone(),
+            report_threshold: self.report_threshold,
+            started_from: Mutex::new(Some((self.tables.clone(), base))),
+        }))
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for MemoryStore {
+    async fn commit(&self) -> Result<(), Error> {
+        match self.finish() {
+            Some((tables, base)) => {
+                let work = self.tables();
+                tables
+                    .lock()
+                    .expect("memory store lock poisoned")
+                    .merge(&base, &work);
+                Ok(())
+            }
+            None => Err(Error::DatabaseQueryError(sqlx::Error::PoolClosed)),
+        }
+    }
+
+    async fn rollback(&self) -> Result<(), Error> {
+        match self.finish() {
+            Some(_) => Ok(()),
+            None => Err(Error::DatabaseQueryError(sqlx::Error::PoolClosed)),
+        }
+    }
+}
+
+#[async_trait]
+impl QuestionRepository for MemoryStore {
+    async fn is_question_owner(
+        &self,
+        question_id: i32,
+        account_id: &AccountId,
+    ) -> Result<bool, Error> {
+        match self
+            .tables()
+            .questions
+            .iter()
+            .find(|stored| stored.question.id.0 == question_id)
+        {
+            Some(stored) => Ok(&stored.question.author.id == account_id),
+            None => Err(Error::NotFound),
+        }
+    }
+
+    async fn add_question(
+        &self,
+        new_question: NewQuestion,
+        account_id: AccountId,
+        flagged: bool,
+    ) -> Result<Question, Error> {
+        let mut tables = self.tables();
+        tables.check_account(&account_id, "questions_account_id_fkey")?;
+        let now = Utc::now();
+        let question = Question {
+            id: QuestionId(tables.next_id(Table::Questions)),
+            title: new_question.title,
+            content: new_question.content,
+            tags: new_question.tags,
+            author: Author {
+                id: account_id,
+                display_name: None,
+            },
+            created_on: now,
+            updated_on: now,
+            moderation_status: if flagged {
+                ModerationStatus::Pending
+            } else {
+                ModerationStatus::Approved
+            },
+            version: 1,
+        };
+
+        tables.questions.push(StoredQuestion {
+            question,
+            deleted_at: None,
+        });
+        let stored = tables.questions.last().expect("question was just added");
+        Ok(tables.question(stored))
+    }
+
+    async fn get_questions(&self, limit: Option<i32>, offset: i32) -> Result<Vec<Question>, Error> {
+        let tables = self.tables();
+        let questions = tables
+            .questions
+            .iter()
+            .filter(|stored| {
+                stored.deleted_at.is_none()
+                    && stored.question.moderation_status == ModerationStatus::Approved
+            })
+            .skip(usize::try_from(offset).unwrap_or(0))
+            .take(limit.map_or(usize::MAX, |limit| usize::try_from(limit).unwrap_or(0)))
+            .map(|stored| tables.question(stored))
+            .collect();
+
+        Ok(questions)
+    }
+
+    async fn get_question_by_id(&self, question_id: i32) -> Result<Question, Error> {
+        let tables = self.tables();
+        match tables.live_question(question_id) {
+            Some(stored) => Ok(tables.question(stored)),
+            None => Err(Error::NotFound),
+        }
+    }
+
+    /// There is no replica to lag behind
+    async fn get_question_by_id_primary(&self, question_id: i32) -> Result<Question, Error> {
+        self.get_question_by_id(question_id).await
+    }
+
+    async fn find_question(
+        &self,
+        account_id: &AccountId,
+        title: &str,
+    ) -> Result<Option<Question>, Error> {
+        let tables = self.tables();
+        Ok(tables
+            .questions
+            .iter()
+            .find(|stored| {
+                stored.question.author.id == *account_id
+                    && stored.question.title == title
+                    && stored.deleted_at.is_none()
+            })
+            .map(|stored| tables.question(stored)))
+    }
+
+    async fn update_question(
+        &self,
+        question: NewQuestion,
+        id: i32,
+        expected_versions: Option<Vec<i32>>,
+        flagged: bool,
+    ) -> Result<Question, Error> {
+        self.write_question(id, expected_versions.as_ref(), flagged, |stored| {
+            stored.title = question.title;
+            stored.content = question.content;
+            stored.tags = question.tags;
+        })
+    }
+
+    async fn patch_question(
+        &self,
+        patch: QuestionPatch,
+        id: i32,
+        expected_versions: Option<Vec<i32>>,
+        flagged: bool,
+    ) -> Result<Question, Error> {
+        self.write_question(id, expected_versions.as_ref(), flagged, |stored| {
+            if let Some(title) = patch.title {
+                stored.title = title;
+            }
+            if let Some(content) = patch.content {
+                stored.content = content;
+            }
+            if let Some(tags) = patch.tags {
+                stored.tags = tags;
+            }
+        })
+    }
+
+    async fn delete_question(
+        &self,
+        id: i32,
+        account_id: AccountId,
+        expected_versions: Option<Vec<i32>>,
+    ) -> Result<bool, Error> {
+        let mut tables = self.tables();
+        match tables.live_question_mut(id).filter(|stored| {
+            stored.question.author.id == account_id
+                && matches_version(&stored.question, expected_versions.as_ref())
+        }) {
+            Some(stored) => {
+                stored.deleted_at = Some(Utc::now());
+                Ok(true)
+            }
+            None => match tables.conditional_write_error(id) {
+                Error::NotFound => Ok(false),
+                error => Err(error),
+            },
+        }
+    }
+
+    async fn restore_question(&self, id: i32, account_id: AccountId) -> Result<Question, Error> {
+        let mut tables = self.tables();
+        match tables.questions.iter_mut().find(|stored| {
+            stored.question.id.0 == id
+                && stored.question.author.id == account_id
+                && stored.deleted_at.is_some()
+        }) {
+            Some(stored) => {
+                stored.deleted_at = None;
+                let stored = tables
+                    .live_question(id)
+                    .expect("question was just restored");
+                Ok(tables.question(stored))
+            }
+            None => Err(Error::NotFound),
+        }
+    }
+
+    async fn purge_deleted_questions(&self, retention_days: i32) -> Result<u64, Error> {
+        let mut tables = self.tables();
+        let cutoff = Utc::now() - Duration::days(i64::from(retention_days));
+        let (purged, kept): (Vec<_>, Vec<_>) = tables
+            .questions
+            .drain(..)
+            .partition(|stored| stored.deleted_at.is_some_and(|deleted| deleted < cutoff));
+
+        tables.questions = kept;
+        let purged: Vec<QuestionId> = purged
+            .into_iter()
+            .map(|stored| stored.question.id)
+            .collect();
+        let answers: Vec<AnswerId> = tables
+            .answers
+            .iter()
+            .filter(|stored| purged.contains(&stored.answer.question_id))
+            .map(|stored| stored.answer.id.clone())
+            .collect();
+
+        tables
+            .answers
+            .retain(|stored| !answers.contains(&stored.answer.id));
+        tables.reports.retain(|report| {
+            !report
+                .question_id
+                .as_ref()
+                .is_some_and(|id| purged.contains(id))
+                && !report
+                    .answer_id
+                    .as_ref()
+                    .is_some_and(|id| answers.contains(id))
+        });
+
+        Ok(purged.len() as u64)
+    }
+
+    async fn add_answer(
+        &self,
+        answer: Answer,
+        account_id: AccountId,
+        flagged: bool,
+    ) -> Result<bool, Error> {
+        let mut tables = self.tables();
+        if !tables
+            .questions
+            .iter()
+            .any(|stored| stored.question.id.0 == answer.question_id && stored.deleted_at.is_none())
+        {
+            return Err(Error::NotFound);
+        }
+        tables.check_account(&account_id, "answers_account_id_fkey")?;
+
+        let answer = PostedAnswer {
+            id: AnswerId(tables.next_id(Table::Answers)),
+            content: answer.content,
+            question_id: QuestionId(answer.question_id),
+            created_on: Utc::now(),
+            moderation_status: if flagged {
+                ModerationStatus::Pending
+            } else {
+                ModerationStatus::Approved
+            },
+        };
+        tables.answers.push(StoredAnswer { answer, account_id });
+        Ok(true)
+    }
+
+    async fn answer_exists(
+        &self,
+        question_id: i32,
+        account_id: &AccountId,
+        content: &str,
+    ) -> Result<bool, Error> {
+        Ok(self.tables().answers.iter().any(|stored| {
+            stored.answer.question_id.0 == question_id
+                && stored.account_id == *account_id
+                && stored.answer.content == content
+        }))
+    }
+}
+
+#[async_trait]
+impl ModerationRepository for MemoryStore {
+    async fn get_moderation_queue(&self) -> Result<ModeratedPosts, Error> {
+        let tables = self.tables();
+        let questions = tables
+            .questions
+            .iter()
+            .filter(|stored| {
+                stored.deleted_at.is_none()
+                    && stored.question.moderation_status == ModerationStatus::Pending
+            })
+            .map(|stored| tables.question(stored))
+            .collect();
+        let answers = tables
+            .answers
+            .iter()
+            .map(|stored| &stored.answer)
+            .filter(|answer| {
+                answer.moderation_status == ModerationStatus::Pending
+                    && tables.on_live_question(answer)
+            })
+            .cloned()
+            .collect();
+
+        Ok(ModeratedPosts { questions, answers })
+    }
+
+    async fn get_unapproved_posts(&self, account_id: &AccountId) -> Result<ModeratedPosts, Error> {
+        let tables = self.tables();
+        let questions = tables
+            .questions
+            .iter()
+            .rev()
+            .filter(|stored| {
+                &stored.question.author.id == account_id
+                    && stored.deleted_at.is_none()
+                    && stored.question.moderation_status != ModerationStatus::Approved
+            })
+            .map(|stored| tables.question(stored))
+            .collect();
+        let answers = tables
+            .answers
+            .iter()
+            .rev()
+            .filter(|stored| {
+                &stored.account_id == account_id
+                    && stored.answer.moderation_status != ModerationStatus::Approved
+                    && tables.on_live_question(&stored.answer)
+            })
+            .map(|stored| stored.answer.clone())
+            .collect();
+
+        Ok(ModeratedPosts { questions, answers })
+    }
+
+    async fn set_question_status(
+        &self,
+        id: i32,
+        status: ModerationStatus,
+    ) -> Result<Question, Error> {
+        let mut tables = self.tables();
+        match tables.live_question_mut(id) {
+            Some(stored) => {
+                stored.question.moderation_status = status;
+                let stored = tables.live_question(id).expect("question was just updated");
+                Ok(tables.question(stored))
+            }
+            None => Err(Error::NotFound),
+        }
+    }
+
+    async fn set_answer_status(
+        &self,
+        id: i32,
+        status: ModerationStatus,
+    ) -> Result<PostedAnswer, Error> {
+        let mut tables = self.tables();
+        match tables
+            .answers
+            .iter_mut()
+            .find(|stored| stored.answer.id.0 == id)
+        {
+            Some(stored) => {
+                stored.answer.moderation_status = status;
+                Ok(stored.answer.clone())
+            }
+            None => Err(Error::NotFound),
+        }
+    }
+
+    async fn add_report(
+        &self,
+        target: ReportTarget,
+        reporter: AccountId,
+        report: NewReport,
+    ) -> Result<bool, Error> {
+        let mut tables = self.tables();
+        let Some((status, author)) = tables.post(target) else {
+            return Err(Error::NotFound);
+        };
+        if author == reporter {
+            return Err(Error::InvalidInput(
+                "authors cannot report their own posts".to_string(),
+            ));
+        }
+        tables.check_account(&reporter, "reports_reporter_id_fkey")?;
+
+        if tables
+            .reports
+            .iter()
+            .any(|stored| stored.reporter == reporter && reported(stored, target))
+        {
+            return Ok(false);
+        }
+
+        let (question_id, answer_id) = match target {
+            ReportTarget::Question(id) => (Some(QuestionId(id)), None),
+            ReportTarget::Answer(id) => (None, Some(AnswerId(id))),
+        };
+        let id = ReportId(tables.next_id(Table::Reports));
+        tables.reports.push(Report {
+            id,
+            reporter,
+            question_id,
+            answer_id,
+            reason: report.reason,
+            note: report.note,
+            created_on: Utc::now(),
+            resolved_on: None,
+            resolution: None,
+        });
+
+        let open = count(
+            tables
+                .reports
+                .iter()
+                .filter(|report| reported(report, target) && report.resolved_on.is_none()),
+        );
+        if open >= self.report_threshold.0 && status == ModerationStatus::Approved {
+            tables.set_status(target, ModerationStatus::Pending);
+        }
+        Ok(true)
+    }
+
+    async fn get_open_reports(&self) -> Result<Vec<Report>, Error> {
+        Ok(self
+            .tables()
+            .reports
+            .iter()
+            .filter(|report| report.resolved_on.is_none())
+            .cloned()
+            .collect())
+    }
+
+    async fn resolve_report(
+        &self,
+        id: i32,
+        action: ReportAction,
+        _moderator: AccountId,
+    ) -> Result<Vec<Report>, Error> {
+        let mut tables = self.tables();
+        let Some(report) = tables
+            .reports
+            .iter()
+            .find(|report| report.id.0 == id && report.resolved_on.is_none())
+        else {
+            return Err(Error::NotFound);
+        };
+
+        let target = match (&report.question_id, &report.answer_id) {
+            (Some(question_id), _) => ReportTarget::Question(question_id.0),
+            (None, Some(answer_id)) => ReportTarget::Answer(answer_id.0),
+            (None, None) => return Err(Error::NotFound),
+        };
+        tables.set_status(target, action.status());
+
+        Ok(tables.resolve_reports(target, action))
+    }
+
+    async fn resolve_reports(
+        &self,
+        target: ReportTarget,
+        action: ReportAction,
+        _moderator: AccountId,
+    ) -> Result<Vec<Report>, Error> {
+        Ok(self.tables().resolve_reports(target, action))
+    }
+}
+
+#[async_trait]
+impl AccountRepository for MemoryStore {
+    async fn add_account(&self, account: Account) -> Result<bool, Error> {
+        let mut tables = self.tables();
+        if tables
+            .accounts
+            .iter()
+            .any(|stored| same_email(&stored.account.email, &account.email))
+        {
+            return Err(Error::UniqueViolation(Some(
+                "accounts_email_lower".to_string(),
+            )));
+        }
+
+        let id = AccountId(tables.next_id(Table::Accounts));
+        tables.accounts.push(StoredAccount {
+            account: Credentials {
+                id,
+                email: account.email,
+                password: Some(account.password),
+            },
+            profile: ProfileUpdate::default(),
+            joined_on: Utc::now(),
+            is_moderator: false,
+            is_admin: false,
+        });
+        Ok(true)
+    }
+
+    async fn get_account(&self, email: String) -> Result<Credentials, Error> {
+        match self
+            .tables()
+            .accounts
+            .iter()
+            .find(|stored| same_email(&stored.account.email, &email))
+        {
+            Some(stored) => Ok(stored.account.clone()),
+            None => Err(Error::NotFound),
+        }
+    }
+
+    async fn get_profile(&self, account_id: i32) -> Result<Profile, Error> {
+        let tables = self.tables();
+        let Some(account) = tables.account(account_id) else {
+            return Err(Error::NotFound);
+        };
+        let id = AccountId(account_id);
+        let approved = |status: ModerationStatus| status == ModerationStatus::Approved;
+
+        let questions = tables
+            .questions
+            .iter()
+            .rev()
+            .filter(|stored| {
+                stored.question.author.id == id
+                    && stored.deleted_at.is_none()
+                    && approved(stored.question.moderation_status)
+            })
+            .map(|stored| tables.question(stored))
+            .collect();
+        let answers = tables
+            .answers
+            .iter()
+            .rev()
+            .filter(|stored| {
+                stored.account_id == id
+                    && approved(stored.answer.moderation_status)
+                    && tables
+                        .live_question(stored.answer.question_id.0)
+                        .is_some_and(|question| approved(question.question.moderation_status))
+            })
+            .map(|stored| stored.answer.clone())
+            .collect();
+
+        Ok(Profile {
+            id,
+            display_name: account.profile.display_name.clone(),
+            bio: account.profile.bio.clone(),
+            avatar_url: account.profile.avatar_url.clone(),
+            joined_on: account.joined_on,
+            questions,
+            answers,
+        })
+    }
+
+    async fn update_profile(
+        &self,
+        account_id: AccountId,
+        profile: ProfileUpdate,
+    ) -> Result<bool, Error> {
+        match self
+            .tables()
+            .accounts
+            .iter_mut()
+            .find(|stored| stored.account.id == account_id)
+        {
+            Some(stored) => {
+                stored.profile = profile;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Computed on every read instead of being stored with the account
+    async fn get_reputation(&self, account_id: i32) -> Result<i64, Error> {
+        let tables = self.tables();
+        match tables.account(account_id) {
+            Some(_) => Ok(self
+                .reputation_rules
+                .score(&tables.contributions(&AccountId(account_id)))),
+            None => Err(Error::NotFound),
+        }
+    }
+
+    async fn is_moderator(&self, account_id: &AccountId) -> Result<bool, Error> {
+        Ok(self
+            .tables()
+            .accounts
+            .iter()
+            .find(|stored| &stored.account.id == account_id)
+            .is_some_and(|stored| stored.is_moderator))
+    }
+
+    async fn is_admin(&self, account_id: &AccountId) -> Result<bool, Error> {
+        Ok(self
+            .tables()
+            .accounts
+            .iter()
+            .find(|stored| &stored.account.id == account_id)
+            .is_some_and(|stored| stored.is_admin))
+    }
+}
+
+#[async_trait]
+impl TransferRepository for MemoryStore {
+    fn export(&self, with_passwords: bool) -> BoxStream<'static, Result<Record, Error>> {
+        let tables = self.tables().clone();
+
+        let accounts = tables.accounts.into_iter().map(move |stored| {
+            Record::Account(AccountRecord {
+                id: stored.account.id,
+                email: stored.account.email,
+                password: stored.account.password.filter(|_| with_passwords),
+                display_name: stored.profile.display_name,
+                bio: stored.profile.bio,
+                avatar_url: stored.profile.avatar_url,
+                is_moderator: stored.is_moderator,
+                is_admin: stored.is_admin,
+                created_on: stored.joined_on,
+            })
+        });
+        let questions = tables.questions.into_iter().map(|stored| {
+            Record::Question(QuestionRecord {
+                id: stored.question.id,
+                account_id: stored.question.author.id,
+                title: stored.question.title,
+                content: stored.question.content,
+                tags: stored.question.tags,
+                moderation_status: stored.question.moderation_status,
+                version: stored.question.version,
+                created_on: stored.question.created_on,
+                updated_on: stored.question.updated_on,
+                deleted_at: stored.deleted_at,
+            })
+        });
+        let answers = tables.answers.into_iter().map(|stored| {
+            Record::Answer(AnswerRecord {
+                id: stored.answer.id,
+                question_id: stored.answer.question_id,
+                account_id: stored.account_id,
+                content: stored.answer.content,
+                moderation_status: stored.answer.moderation_status,
+                created_on: stored.answer.created_on,
+            })
+        });
+
+        stream::iter(accounts.chain(questions).chain(answers).map(Ok)).boxed()
+    }
+
+    async fn import(&self, record: Record) -> Result<(), Error> {
+        let mut tables = self.tables();
+        match record {
+            Record::Account(account) => {
+                if tables.account(account.id.0).is_some() {
+                    return Err(Error::UniqueViolation(Some("accounts_pkey".to_string())));
+                }
+                if tables
+                    .accounts
+                    .iter()
+                    .any(|stored| same_email(&stored.account.email, &account.email))
+                {
+                    return Err(Error::UniqueViolation(Some(
+                        "accounts_email_lower".to_string(),
+                    )));
+                }
+
+                tables.accounts.push(StoredAccount {
+                    account: Credentials {
+                        id: account.id,
+                        email: account.email,
+                        password: account.password,
+                    },
+                    profile: ProfileUpdate {
+                        display_name: account.display_name,
+                        bio: account.bio,
+                        avatar_url: account.avatar_url,
+                    },
+                    joined_on: account.created_on,
+                    is_moderator: account.is_moderator,
+                    is_admin: account.is_admin,
+                });
+            }
+            Record::Question(question) => {
+                if tables
+                    .questions
+                    .iter()
+                    .any(|stored| stored.question.id == question.id)
+                {
+                    return Err(Error::UniqueViolation(Some("questions_pkey".to_string())));
+                }
+                tables.check_account(&question.account_id, "questions_account_id_fkey")?;
+
+                tables.questions.push(StoredQuestion {
+                    question: Question {
+                        id: question.id,
+                        title: question.title,
+                        content: question.content,
+                        tags: question.tags,
+                        author: Author {
+                            id: question.account_id,
+                            display_name: None,
+                        },
+                        created_on: question.created_on,
+                        updated_on: question.updated_on,
+                        moderation_status: question.moderation_status,
+                        version: question.version,
+                    },
+                    deleted_at: question.deleted_at,
+                });
+            }
+            Record::Answer(answer) => {
+                if tables
+                    .answers
+                    .iter()
+                    .any(|stored| stored.answer.id == answer.id)
+                {
+                    return Err(Error::UniqueViolation(Some("answers_pkey".to_string())));
+                }
+                if !tables
+                    .questions
+                    .iter()
+                    .any(|stored| stored.question.id == answer.question_id)
+                {
+                    return Err(Error::ForeignKeyViolation(Some(
+                        "answers_corresponding_question_fkey".to_string(),
+                    )));
+                }
+                tables.check_account(&answer.account_id, "answers_account_id_fkey")?;
+
+                tables.answers.push(StoredAnswer {
+                    answer: PostedAnswer {
+                        id: answer.id,
+                        content: answer.content,
+                        question_id: answer.question_id,
+                        created_on: answer.created_on,
+                        moderation_status: answer.moderation_status,
+                    },
+                    account_id: answer.account_id,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    async fn finish_import(&self) -> Result<(), Error> {
+        let tables = self.tables();
+        let last_ids = [
+            tables.accounts.iter().map(|s| s.account.id.0).max(),
+            tables.questions.iter().map(|s| s.question.id.0).max(),
+            tables.answers.iter().map(|s| s.answer.id.0).max(),
+            tables.reports.iter().map(|report| report.id.0).max(),
+        ];
+        let mut sequences = tables.last_ids.lock().expect("memory store lock poisoned");
+        for (last_id, imported) in sequences.iter_mut().zip(last_ids) {
+            *last_id = (*last_id).max(imported.unwrap_or(0));
+        }
+        // Reputation is computed when it is read
+        Ok(())
+    }
+}
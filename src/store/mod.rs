@@ -0,0 +1,462 @@
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use std::{fmt::Debug, future::Future, ops::Deref, sync::Arc};
+
+use handle_errors::Error;
+
+use crate::types::{
+    account::{Account, AccountId, Credentials, Profile, ProfileUpdate},
+    answer::{Answer, PostedAnswer},
+    export::Record,
+    moderation::{ModeratedPosts, ModerationStatus},
+    question::{NewQuestion, Question, QuestionPatch},
+    report::{NewReport, Report, ReportAction, ReportTarget},
+};
+
+#[cfg(test)]
+pub mod memory;
+mod postgres;
+mod rows;
+
+pub use postgres::PgStore;
+
+/// The store every handler gets, backed by Postgres when the
+/// server runs and by memory in the handler tests.
+#[derive(Debug, Clone)]
+pub struct Store(Arc<dyn Repository>);
+
+impl Store {
+    pub fn new(repository: Arc<dyn Repository>) -> Self {
+        Store(repository)
+    }
+
+    /// Runs `work` with a store whose methods all take part in one
+    /// transaction. It is committed if `work` succeeds and rolled back
+    /// if it fails, so either all of its writes are stored or none.
+    ///
+    /// ```ignore
+    /// store
+    ///     .transaction(|store| async move {
+    ///         if !store.is_question_owner(id, &account_id).await? {
+    ///             return Err(Error::Unauthorized);
+    ///         }
+    ///         store.delete_question(id, account_id, None).await
+    ///     })
+    ///     .await?;
+    /// ```
+    pub async fn transaction<T, F, Fut>(&self, work: F) -> Result<T, Error>
+    where
+        F: FnOnce(Store) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let unit_of_work = self.0.begin().await?;
+
+        match work(Store(unit_of_work.clone())).await {
+            Ok(value) => {
+                unit_of_work.commit().await?;
+                Ok(value)
+            }
+            Err(error) => {
+                // The error of `work` is the one worth reporting, a failed
+                // rollback is undone by the database when the connection drops
+                if let Err(rollback_error) = unit_of_work.rollback().await {
+                    tracing::event!(tracing::Level::ERROR, "{:?}", rollback_error);
+                }
+                Err(error)
+            }
+        }
+    }
+}
+
+impl Deref for Store {
+    type Target = dyn Repository;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+/// Questions and the answers given to them
+#[async_trait]
+pub trait QuestionRepository: Debug + Send + Sync {
+    /// Locks the question until the end of the unit of work, if
+    /// there is one, so it cannot change before it is written
+    async fn is_question_owner(
+        &self,
+        question_id: i32,
+        account_id: &AccountId,
+    ) -> Result<bool, Error>;
+
+    async fn add_question(
+        &self,
+        new_question: NewQuestion,
+        account_id: AccountId,
+        flagged: bool,
+    ) -> Result<Question, Error>;
+
+    /// Approved questions which are not deleted
+    async fn get_questions(&self, limit: Option<i32>, offset: i32) -> Result<Vec<Question>, Error>;
+
+    async fn get_question_by_id(&self, question_id: i32) -> Result<Question, Error>;
+
+    /// Like `get_question_by_id`, but never reads from a replica, which
+    /// may lag behind the primary
+    async fn get_question_by_id_primary(&self, question_id: i32) -> Result<Question, Error>;
+
+    /// The oldest question of the author with this title which is not
+    /// deleted, whatever its moderation status
+    async fn find_question(
+        &self,
+        account_id: &AccountId,
+        title: &str,
+    ) -> Result<Option<Question>, Error>;
+
+    /// Fails with `PreconditionFailed` if `expected_versions` does not
+    /// contain the current version of the question
+    async fn update_question(
+        &self,
+        question: NewQuestion,
+        id: i32,
+        expected_versions: Option<Vec<i32>>,
+        flagged: bool,
+    ) -> Result<Question, Error>;
+
+    async fn patch_question(
+        &self,
+        patch: QuestionPatch,
+        id: i32,
+        expected_versions: Option<Vec<i32>>,
+        flagged: bool,
+    ) -> Result<Question, Error>;
+
+    /// Soft-deletes the question, returns `false` if there is none
+    async fn delete_question(
+        &self,
+        id: i32,
+        account_id: AccountId,
+        expected_versions: Option<Vec<i32>>,
+    ) -> Result<bool, Error>;
+
+    async fn restore_question(&self, id: i32, account_id: AccountId) -> Result<Question, Error>;
+
+    /// Permanently removes questions which were soft-deleted more than
+    /// `retention_days` ago, together with their answers.
+    /// Returns the number of purged questions.
+    async fn purge_deleted_questions(&self, retention_days: i32) -> Result<u64, Error>;
+
+    /// Fails with `NotFound` if the question does not exist or is deleted
+    async fn add_answer(
+        &self,
+        answer: Answer,
+        account_id: AccountId,
+        flagged: bool,
+    ) -> Result<bool, Error>;
+
+    /// If the author gave an answer with this content to the question
+    async fn answer_exists(
+        &self,
+        question_id: i32,
+        account_id: &AccountId,
+        content: &str,
+    ) -> Result<bool, Error>;
+}
+
+/// Review of flagged and reported posts
+#[async_trait]
+pub trait ModerationRepository: Debug + Send + Sync {
+    /// Pending posts of all authors, oldest first
+    async fn get_moderation_queue(&self) -> Result<ModeratedPosts, Error>;
+
+    /// Posts of an author which are pending or were rejected
+    async fn get_unapproved_posts(&self, account_id: &AccountId) -> Result<ModeratedPosts, Error>;
+
+    async fn set_question_status(
+        &self,
+        id: i32,
+        status: ModerationStatus,
+    ) -> Result<Question, Error>;
+
+    async fn set_answer_status(
+        &self,
+        id: i32,
+        status: ModerationStatus,
+    ) -> Result<PostedAnswer, Error>;
+
+    /// Stores the report unless the account reported the post before, and
+    /// hides the post until it is reviewed once it has `hide_threshold`
+    /// open reports. Returns if the report is new.
+    async fn add_report(
+        &self,
+        target: ReportTarget,
+        reporter: AccountId,
+        report: NewReport,
+    ) -> Result<bool, Error>;
+
+    /// Reports no moderator resolved yet, oldest first
+    async fn get_open_reports(&self) -> Result<Vec<Report>, Error>;
+
+    /// Approves or rejects the reported post, which resolves all
+    /// open reports of that post. Returns the resolved reports.
+    async fn resolve_report(
+        &self,
+        id: i32,
+        action: ReportAction,
+        moderator: AccountId,
+    ) -> Result<Vec<Report>, Error>;
+
+    /// Resolves the open reports of a post a moderator decided on
+    /// without a report, e.g. from the moderation queue. Leaves the
+    /// status of the post as it is. Returns the resolved reports.
+    async fn resolve_reports(
+        &self,
+        target: ReportTarget,
+        action: ReportAction,
+        moderator: AccountId,
+    ) -> Result<Vec<Report>, Error>;
+}
+
+/// Accounts with their profiles and reputation
+#[async_trait]
+pub trait AccountRepository: Debug + Send + Sync {
+    async fn add_account(&self, account: Account) -> Result<bool, Error>;
+
+    /// Emails are compared regardless of case
+    async fn get_account(&self, email: String) -> Result<Credentials, Error>;
+
+    /// Only approved posts of questions which are not deleted are shown
+    async fn get_profile(&self, account_id: i32) -> Result<Profile, Error>;
+
+    async fn update_profile(
+        &self,
+        account_id: AccountId,
+        profile: ProfileUpdate,
+    ) -> Result<bool, Error>;
+
+    async fn get_reputation(&self, account_id: i32) -> Result<i64, Error>;
+
+    async fn is_moderator(&self, account_id: &AccountId) -> Result<bool, Error>;
+
+    /// Admins can move all records over HTTP, moderators cannot
+    async fn is_admin(&self, account_id: &AccountId) -> Result<bool, Error>;
+}
+
+/// All content as records, to move it between databases
+#[async_trait]
+pub trait TransferRepository: Debug + Send + Sync {
+    /// Every account, then every question and then every answer, each
+    /// ordered by id and read from one snapshot, page by page. Password
+    /// hashes are left out unless `with_passwords` is set.
+    fn export(&self, with_passwords: bool) -> BoxStream<'static, Result<Record, Error>>;
+
+    /// Stores the record with its id, which must not be taken yet
+    async fn import(&self, record: Record) -> Result<(), Error>;
+
+    /// Lets new rows get ids after the imported ones and recomputes the
+    /// reputation of every account
+    async fn finish_import(&self) -> Result<(), Error>;
+}
+
+/// Everything the handlers need from a storage backend
+#[async_trait]
+pub trait Repository:
+    QuestionRepository + ModerationRepository + AccountRepository + TransferRepository
+{
+    /// Starts a unit of work, which is nested in the current one if
+    /// the repository is a unit of work itself
+    async fn begin(&self) -> Result<Arc<dyn UnitOfWork>, Error>;
+}
+
+/// A repository whose writes are only stored once it is committed.
+/// It is finished by `commit` or `rollback` and must not be used
+/// afterwards, `PgStore` fails every later call with `PoolClosed`.
+#[async_trait]
+pub trait UnitOfWork: Repository {
+    async fn commit(&self) -> Result<(), Error>;
+
+    async fn rollback(&self) -> Result<(), Error>;
+}
+
+#[cfg(test)]
+mod store_tests {
+    use super::{memory::MemoryStore, Store};
+    use crate::types::{account::AccountId, answer::Answer, question::NewQuestion};
+    use handle_errors::Error;
+    use std::sync::Arc;
+
+    fn new_question(title: &str) -> NewQuestion {
+        NewQuestion {
+            title: title.to_string(),
+            content: "Content".to_string(),
+            tags: None,
+        }
+    }
+
+    async fn titles(store: &Store) -> Vec<String> {
+        store
+            .get_questions(None, 0)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|question| question.title)
+            .collect()
+    }
+
+    fn store_with_account() -> (Store, AccountId) {
+        let memory = MemoryStore::default();
+        let account_id = memory.create_account("author@email.com");
+        (Store::new(Arc::new(memory)), account_id)
+    }
+
+    #[tokio::test]
+    async fn commit_on_success() {
+        let (store, account_id) = store_with_account();
+
+        let question = store
+            .transaction(|store| async move {
+                store
+                    .add_question(new_question("First"), account_id.clone(), false)
+                    .await?;
+                store
+                    .add_question(new_question("Second"), account_id, false)
+                    .await
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(question.title, "Second");
+        assert_eq!(titles(&store).await, ["First", "Second"]);
+    }
+
+    #[tokio::test]
+    async fn roll_back_on_error() {
+        let (store, account_id) = store_with_account();
+
+        let result = store
+            .transaction(|store| async move {
+                store
+                    .add_question(new_question("First"), account_id, false)
+                    .await?;
+                Err::<(), _>(Error::Unauthorized)
+            })
+            .await;
+
+        assert!(matches!(result, Err(Error::Unauthorized)));
+        assert!(titles(&store).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn roll_back_nested_unit_of_work_only() {
+        let (store, account_id) = store_with_account();
+
+        store
+            .transaction(|store| async move {
+                store
+                    .add_question(new_question("Outer"), account_id.clone(), false)
+                    .await?;
+                let inner = store
+                    .transaction(|store| async move {
+                        store
+                            .add_question(new_question("Inner"), account_id, false)
+                            .await?;
+                        Err::<(), _>(Error::PreconditionFailed)
+                    })
+                    .await;
+                assert!(inner.is_err());
+                Ok(())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(titles(&store).await, ["Outer"]);
+    }
+
+    #[tokio::test]
+    async fn commit_keeps_writes_made_meanwhile() {
+        let (store, account_id) = store_with_account();
+        let outside = store.clone();
+        let outside_account = account_id.clone();
+
+        store
+            .transaction(|store| async move {
+                store
+                    .add_question(new_question("Inside"), account_id, false)
+                    .await?;
+                // Written while the unit of work is still open
+                outside
+                    .add_question(new_question("Outside"), outside_account, false)
+                    .await?;
+                Ok(())
+            })
+            .await
+            .unwrap();
+
+        let questions = store.get_questions(None, 0).await.unwrap();
+        let mut titles: Vec<(i32, String)> = questions
+            .into_iter()
+            .map(|question| (question.id.0, question.title))
+            .collect();
+        titles.sort();
+        assert_eq!(
+            titles,
+            [(1, "Inside".to_string()), (2, "Outside".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn purge_questions_deleted_before_retention() {
+        let (store, account_id) = store_with_account();
+        let first = store
+            .add_question(new_question("First"), account_id.clone(), false)
+            .await
+            .unwrap();
+        store
+            .add_question(new_question("Second"), account_id.clone(), false)
+            .await
+            .unwrap();
+        assert!(store
+            .delete_question(first.id.0, account_id.clone(), None)
+            .await
+            .unwrap());
+
+        // Still within the retention period, so it can be restored
+        assert_eq!(store.purge_deleted_questions(30).await.unwrap(), 0);
+        store
+            .restore_question(first.id.0, account_id.clone())
+            .await
+            .unwrap();
+        assert_eq!(titles(&store).await, ["First", "Second"]);
+
+        store
+            .delete_question(first.id.0, account_id.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(store.purge_deleted_questions(0).await.unwrap(), 1);
+        assert!(matches!(
+            store.restore_question(first.id.0, account_id).await,
+            Err(Error::NotFound)
+        ));
+        assert_eq!(titles(&store).await, ["Second"]);
+    }
+
+    #[tokio::test]
+    async fn deleted_questions_take_no_answers() {
+        let (store, account_id) = store_with_account();
+        let question = store
+            .add_question(new_question("Title"), account_id.clone(), false)
+            .await
+            .unwrap();
+        store
+            .delete_question(question.id.0, account_id.clone(), None)
+            .await
+            .unwrap();
+
+        let answer = Answer {
+            content: "Answer".to_string(),
+            question_id: question.id.0,
+        };
+        assert!(matches!(
+            store.add_answer(answer, account_id, false).await,
+            Err(Error::NotFound)
+        ));
+    }
+}
@@ -0,0 +1,1568 @@
+use async_trait::async_trait;
+use futures::{
+    future::BoxFuture,
+    stream::{self, BoxStream, StreamExt, TryStreamExt},
+};
+use sqlx::{
+    pool::PoolConnection,
+    postgres::{PgConnectOptions, PgConnection, PgPool, PgPoolOptions},
+    Connection, Executor, Postgres, Transaction,
+};
+use std::{
+    borrow::Cow,
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tokio::sync::{Mutex, MutexGuard};
+
+use handle_errors::Error;
+
+use super::rows::{
+    moderated_posts, AccountExportRow, AccountRow, AnswerRow, ProfileRow, QuestionExportRow,
+    QuestionRow, ReportRow,
+};
+use super::{
+    AccountRepository, ModerationRepository, QuestionRepository, Repository, TransferRepository,
+    UnitOfWork,
+};
+use crate::reputation::{Contributions, ReputationRules};
+use crate::types::{
+    account::{Account, AccountId, Credentials, Profile, ProfileUpdate},
+    answer::{Answer, PostedAnswer},
+    export::Record,
+    moderation::{ModeratedPosts, ModerationStatus},
+    question::{NewQuestion, Question, QuestionPatch},
+    report::{NewReport, Report, ReportAction, ReportTarget, ReportThreshold},
+};
+
+/// Logs a failed query and classifies it. A missing row is what callers
+/// ask for, constraint violations are the client's fault, so only the
+/// rest is logged as an error.
+fn query_error(error: sqlx::Error) -> Error {
+    let (code, db_message, constraint) = match error.as_database_error() {
+        Some(db_error) => (
+            db_error.code().map(Cow::into_owned),
+            Some(db_error.message().to_string()),
+            db_error.constraint().map(str::to_string),
+        ),
+        None => (None, None, None),
+    };
+
+    let error = Error::from(error);
+    match &error {
+        Error::NotFound => tracing::event!(tracing::Level::DEBUG, "No matching row"),
+        Error::UniqueViolation(_) | Error::ForeignKeyViolation(_) => tracing::event!(
+            tracing::Level::WARN,
+            code = ?code,
+            db_message = ?db_message,
+            constraint = ?constraint
+        ),
+        _ => tracing::event!(
+            tracing::Level::ERROR,
+            code = ?code,
+            db_message = ?db_message,
+            constraint = ?constraint,
+            "{:?}",
+            error
+        ),
+    }
+    error
+}
+
+/// The `question_id` and `answer_id` of a report on the post
+fn report_columns(target: ReportTarget) -> (Option<i32>, Option<i32>) {
+    match target {
+        ReportTarget::Question(id) => (Some(id), None),
+        ReportTarget::Answer(id) => (None, Some(id)),
+    }
+}
+
+/// Marks the open reports of the post as resolved by the moderator
+async fn close_reports(
+    tx: &mut Transaction<'_, Postgres>,
+    target: ReportTarget,
+    action: ReportAction,
+    moderator: &AccountId,
+) -> Result<Vec<Report>, sqlx::Error> {
+    let (question_id, answer_id) = report_columns(target);
+    let reports = sqlx::query_as!(
+        ReportRow,
+        "UPDATE reports SET resolved_on = NOW(), resolved_by = $1, resolution = $2
+        WHERE (question_id = $3 OR answer_id = $4) AND resolved_on IS NULL
+        RETURNING id, reporter_id, question_id, answer_id, reason, note,
+        created_on, resolved_on, resolution",
+        moderator.0,
+        action.as_str(),
+        question_id,
+        answer_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    Ok(reports.into_iter().map(Report::from).collect())
+}
+
+/// A question which is not deleted
+async fn question_by_id(
+    connection: &mut PgConnection,
+    question_id: i32,
+) -> Result<QuestionRow, sqlx::Error> {
+    sqlx::query_as!(
+        QuestionRow,
+        "SELECT questions.id, title, content, tags, account_id, accounts.display_name,
+        questions.created_on, updated_on, moderation_status, version FROM questions
+        LEFT JOIN accounts ON accounts.id = questions.account_id
+        WHERE questions.id = $1 AND questions.deleted_at IS NULL",
+        question_id
+    )
+    .fetch_one(connection)
+    .await
+}
+
+/// Tells apart why a conditional write on a question matched no row:
+/// either the question is gone or its version did not match.
+async fn conditional_write_error(connection: &mut PgConnection, id: i32) -> Error {
+    match sqlx::query_scalar!(
+        "SELECT id FROM questions WHERE id = $1 AND deleted_at IS NULL",
+        id
+    )
+    .fetch_optional(connection)
+    .await
+    {
+        Ok(Some(_)) => Error::PreconditionFailed,
+        Ok(None) => Error::NotFound,
+        Err(e) => query_error(e),
+    }
+}
+
+/// The transaction of a unit of work. Units of work nested in it share
+/// the transaction and each run in a savepoint of their own.
+#[derive(Debug)]
+struct PgUnitOfWork {
+    transaction: Arc<Mutex<Option<Transaction<'static, Postgres>>>>,
+    /// `None` for the outermost unit of work, which owns the transaction
+    savepoint: Option<String>,
+    depth: usize,
+    finished: AtomicBool,
+}
+
+impl PgUnitOfWork {
+    fn new(transaction: Transaction<'static, Postgres>) -> Self {
+        PgUnitOfWork {
+            transaction: Arc::new(Mutex::new(Some(transaction))),
+            savepoint: None,
+            depth: 1,
+            finished: AtomicBool::new(false),
+        }
+    }
+
+    /// Waits until no other call uses the transaction
+    async fn lock(&self) -> Result<PgHandle<'_>, sqlx::Error> {
+        let transaction = self.transaction.lock().await;
+        if transaction.is_none() || self.finished.load(Ordering::SeqCst) {
+            return Err(sqlx::Error::PoolClosed);
+        }
+        Ok(PgHandle::Shared(transaction))
+    }
+
+    /// Runs unprepared statements, like the ones for savepoints
+    async fn execute(&self, sql: &str) -> Result<(), sqlx::Error> {
+        let mut connection = self.lock().await?;
+        connection.execute(sql).await?;
+        Ok(())
+    }
+
+    async fn nested(&self) -> Result<Self, sqlx::Error> {
+        let depth = self.depth + 1;
+        let savepoint = format!("unit_of_work_{depth}");
+        self.execute(&format!("SAVEPOINT {savepoint}")).await?;
+
+        Ok(PgUnitOfWork {
+            transaction: self.transaction.clone(),
+            savepoint: Some(savepoint),
+            depth,
+            finished: AtomicBool::new(false),
+        })
+    }
+
+    async fn commit(&self) -> Result<(), sqlx::Error> {
+        match &self.savepoint {
+            Some(savepoint) => {
+                self.execute(&format!("RELEASE SAVEPOINT {savepoint}"))
+                    .await?;
+            }
+            None => match self.transaction.lock().await.take() {
+                Some(transaction) => transaction.commit().await?,
+                None => return Err(sqlx::Error::PoolClosed),
+            },
+        }
+        self.finished.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn rollback(&self) -> Result<(), sqlx::Error> {
+        // Finished even if the rollback fails, the transaction is not
+        // usable anymore either way
+        if let Some(savepoint) = &self.savepoint {
+            let rolled_back = self
+                .execute(&format!(
+                    "ROLLBACK TO SAVEPOINT {savepoint}; RELEASE SAVEPOINT {savepoint}"
+                ))
+                .await;
+            self.finished.store(true, Ordering::SeqCst);
+            return rolled_back;
+        }
+
+        self.finished.store(true, Ordering::SeqCst);
+        match self.transaction.lock().await.take() {
+            Some(transaction) => transaction.rollback().await,
+            None => Err(sqlx::Error::PoolClosed),
+        }
+    }
+}
+
+/// The connection a call runs on: its own one from the pool, or the
+/// one of the unit of work, which is locked until the call returns
+enum PgHandle<'a> {
+    Pooled(Box<PoolConnection<Postgres>>),
+    /// Only handed out while the transaction is still open
+    Shared(MutexGuard<'a, Option<Transaction<'static, Postgres>>>),
+}
+
+impl Deref for PgHandle<'_> {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            PgHandle::Pooled(connection) => connection,
+            PgHandle::Shared(transaction) => transaction.as_ref().expect("transaction is open"),
+        }
+    }
+}
+
+impl DerefMut for PgHandle<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            PgHandle::Pooled(connection) => connection,
+            PgHandle::Shared(transaction) => transaction.as_mut().expect("transaction is open"),
+        }
+    }
+}
+
+/// Rows fetched by one query of an export
+const EXPORT_PAGE_SIZE: i64 = 500;
+
+#[derive(Debug, Clone, Copy)]
+enum ExportTable {
+    Accounts,
+    Questions,
+    Answers,
+}
+
+/// Where an export is, it reads all pages in one read-only transaction
+/// so they fit together even while the tables change
+struct Export {
+    pool: PgPool,
+    transaction: Option<Transaction<'static, Postgres>>,
+    /// `None` once all tables are exported
+    table: Option<ExportTable>,
+    /// Id of the last exported row of the table
+    after: i32,
+    with_passwords: bool,
+}
+
+impl Export {
+    /// The next rows by id, `None` once everything was exported
+    async fn next_page(&mut self) -> Result<Option<Vec<Record>>, sqlx::Error> {
+        loop {
+            let Some(table) = self.table else {
+                if let Some(transaction) = self.transaction.take() {
+                    transaction.commit().await?;
+                }
+                return Ok(None);
+            };
+
+            let transaction = if let Some(transaction) = &mut self.transaction {
+                transaction
+            } else {
+                let mut transaction = self.pool.begin().await?;
+                transaction
+                    .execute("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+                    .await?;
+                self.transaction.insert(transaction)
+            };
+
+            // Postgres does not report the nullability of columns under a
+            // `LIMIT`, so the ones which are never NULL are marked with `!`
+            let (page, last_id): (Vec<Record>, Option<i32>) = match table {
+                ExportTable::Accounts => {
+                    let rows = sqlx::query_as!(
+                        AccountExportRow,
+                        r#"SELECT id AS "id!", email AS "email!",
+                        CASE WHEN $2 THEN password END AS "password?",
+                        display_name, bio, avatar_url, is_moderator AS "is_moderator!",
+                        is_admin AS "is_admin!", created_on AS "created_on!" FROM accounts
+                        WHERE id > $1 ORDER BY id LIMIT $3"#,
+                        self.after,
+                        self.with_passwords,
+                        EXPORT_PAGE_SIZE
+                    )
+                    .fetch_all(&mut *transaction)
+                    .await?;
+                    let last_id = rows.last().map(|row| row.id);
+                    (rows.into_iter().map(Record::from).collect(), last_id)
+                }
+                ExportTable::Questions => {
+                    let rows = sqlx::query_as!(
+                        QuestionExportRow,
+                        r#"SELECT id AS "id!", account_id AS "account_id!", title AS "title!",
+                        content AS "content!", tags, moderation_status AS "moderation_status!",
+                        version AS "version!", created_on AS "created_on!",
+                        updated_on AS "updated_on!", deleted_at FROM questions
+                        WHERE id > $1 ORDER BY id LIMIT $2"#,
+                        self.after,
+                        EXPORT_PAGE_SIZE
+                    )
+                    .fetch_all(&mut *transaction)
+                    .await?;
+                    let last_id = rows.last().map(|row| row.id);
+                    (rows.into_iter().map(Record::from).collect(), last_id)
+                }
+                ExportTable::Answers => {
+                    let rows = sqlx::query_as!(
+                        AnswerRow,
+                        r#"SELECT id AS "id!", content AS "content!",
+                        corresponding_question AS "corresponding_question!",
+                        account_id AS "account_id!", created_on AS "created_on!",
+                        moderation_status AS "moderation_status!" FROM answers
+                        WHERE id > $1 ORDER BY id LIMIT $2"#,
+                        self.after,
+                        EXPORT_PAGE_SIZE
+                    )
+                    .fetch_all(&mut *transaction)
+                    .await?;
+                    let last_id = rows.last().map(|row| row.id);
+                    (rows.into_iter().map(Record::from).collect(), last_id)
+                }
+            };
+
+            if let Some(last_id) = last_id {
+                self.after = last_id;
+                return Ok(Some(page));
+            }
+
+            self.after = 0;
+            self.table = match table {
+                ExportTable::Accounts => Some(ExportTable::Questions),
+                ExportTable::Questions => Some(ExportTable::Answers),
+                ExportTable::Answers => None,
+            };
+        }
+    }
+}
+
+/// The Postgres backed store the server runs with
+#[derive(Debug, Clone)]
+pub struct PgStore {
+    pub connection: PgPool,
+    /// Serves the reads which may lag behind the writes, see `read`
+    replica: Option<PgPool>,
+    pub reputation_rules: ReputationRules,
+    report_threshold: ReportThreshold,
+    /// Set for the stores handed out by `Repository::begin`
+    unit_of_work: Option<Arc<PgUnitOfWork>>,
+}
+
+impl PgStore {
+    pub async fn new(
+        connect_options: PgConnectOptions,
+        pool_options: PgPoolOptions,
+    ) -> Result<Self, sqlx::Error> {
+        let db_pool = pool_options.connect_with(connect_options).await?;
+
+        Ok(PgStore {
+            connection: db_pool,
+            replica: None,
+            reputation_rules: ReputationRules::default(),
+            report_threshold: ReportThreshold::default(),
+            unit_of_work: None,
+        })
+    }
+
+    /// Hides a post once it has `report_threshold` open reports
+    #[must_use]
+    pub fn with_report_threshold(mut self, report_threshold: ReportThreshold) -> Self {
+        self.report_threshold = report_threshold;
+        self
+    }
+
+    /// Sends `read` to `replica`, which should be a lazy pool, so the
+    /// store still starts while the replica is down
+    #[must_use]
+    pub fn with_read_replica(mut self, replica: PgPool) -> Self {
+        self.replica = Some(replica);
+        self
+    }
+
+    /// Runs a read-only query on the read replica, falling back to the
+    /// primary if the replica fails. A row which is missing on the
+    /// replica may only not have arrived yet, so it is looked up on the
+    /// primary too. Units of work always read from the primary, to see
+    /// their own writes.
+    async fn read<T, Q>(&self, query: Q) -> Result<T, Error>
+    where
+        T: Send,
+        Q: for<'c> Fn(&'c mut PgConnection) -> BoxFuture<'c, Result<T, sqlx::Error>> + Send + Sync,
+    {
+        if let (Some(replica), None) = (&self.replica, &self.unit_of_work) {
+            let read = match replica.acquire().await {
+                Ok(mut connection) => query(&mut connection).await,
+                Err(e) => Err(e),
+            };
+            match read {
+                Ok(value) => return Ok(value),
+                Err(sqlx::Error::RowNotFound) => {}
+                Err(e) => tracing::event!(
+                    tracing::Level::WARN,
+                    error = %e,
+                    "Read replica failed, reading from the primary"
+                ),
+            }
+        }
+
+        let mut connection = self.acquire().await?;
+        query(&mut connection).await.map_err(query_error)
+    }
+
+    async fn acquire(&self) -> Result<PgHandle<'_>, Error> {
+        let handle = match &self.unit_of_work {
+            Some(unit_of_work) => unit_of_work.lock().await,
+            None => self
+                .connection
+                .acquire()
+                .await
+                .map(|connection| PgHandle::Pooled(Box::new(connection))),
+        };
+
+        handle.map_err(query_error)
+    }
+
+    /// Changes the moderation status of a post, optionally only if it
+    /// currently has the status `from`. Returns if the post was changed.
+    async fn update_post_status(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        target: ReportTarget,
+        status: ModerationStatus,
+        from: Option<ModerationStatus>,
+    ) -> Result<bool, sqlx::Error> {
+        match target {
+            ReportTarget::Question(id) => {
+                let updated = sqlx::query!(
+                    "UPDATE questions SET moderation_status = $1
+                WHERE id = $2 AND ($3::text IS NULL OR moderation_status = $3)",
+                    status.as_str(),
+                    id,
+                    from.map(ModerationStatus::as_str)
+                )
+                .execute(&mut *tx)
+                .await?
+                .rows_affected()
+                    > 0;
+
+                if updated {
+                    self.refresh_question_reputation(tx, id).await?;
+                }
+                Ok(updated)
+            }
+            ReportTarget::Answer(id) => {
+                let account_id = sqlx::query_scalar!(
+                    "UPDATE answers SET moderation_status = $1
+                WHERE id = $2 AND ($3::text IS NULL OR moderation_status = $3)
+                RETURNING account_id",
+                    status.as_str(),
+                    id,
+                    from.map(ModerationStatus::as_str)
+                )
+                .fetch_optional(&mut *tx)
+                .await?;
+
+                if let Some(account_id) = account_id {
+                    self.refresh_reputation(tx, &[account_id]).await?;
+                }
+                Ok(account_id.is_some())
+            }
+        }
+    }
+
+    /// Recomputes the stored reputation of the given accounts. Runs inside
+    /// the transaction which changed their contributions, so the
+    /// denormalized score never disagrees with the data it is derived from.
+    async fn refresh_reputation(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        account_ids: &[i32],
+    ) -> Result<(), sqlx::Error> {
+        // Concurrent writers for the same account queue up here, so each of
+        // them counts the contributions the others committed before.
+        let account_ids = sqlx::query_scalar!(
+            "SELECT id FROM accounts WHERE id = ANY($1) ORDER BY id FOR UPDATE",
+            account_ids
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for account_id in account_ids {
+            let contributions = sqlx::query_as!(
+                Contributions,
+                r#"SELECT
+                (SELECT COUNT(*) FROM questions
+                    WHERE account_id = $1 AND deleted_at IS NULL
+                    AND moderation_status = 'approved') AS "questions_asked!",
+                (SELECT COUNT(*) FROM answers
+                    WHERE account_id = $1 AND moderation_status = 'approved') AS "answers_given!",
+                (SELECT COUNT(*) FROM answers
+                    JOIN questions ON questions.id = answers.corresponding_question
+                    WHERE answers.account_id = $1 AND questions.deleted_at IS NULL
+                    AND answers.moderation_status = 'approved'
+                    AND questions.moderation_status = 'approved'
+                ) AS "answers_on_live_questions!""#,
+                account_id
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+
+            sqlx::query!(
+                "UPDATE accounts SET reputation = $1 WHERE id = $2",
+                self.reputation_rules.score(&contributions),
+                account_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Deleting, restoring or moderating a question changes the reputation
+    /// of its author and of everybody who answered it.
+    async fn refresh_question_reputation(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        question_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        let account_ids = sqlx::query_scalar!(
+            r#"SELECT account_id AS "account_id!" FROM questions WHERE id = $1
+        UNION SELECT account_id FROM answers WHERE corresponding_question = $1"#,
+            question_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        self.refresh_reputation(tx, &account_ids).await
+    }
+}
+
+#[async_trait]
+impl Repository for PgStore {
+    async fn begin(&self) -> Result<Arc<dyn UnitOfWork>, Error> {
+        let unit_of_work = match &self.unit_of_work {
+            Some(unit_of_work) => unit_of_work.nested().await,
+            None => self.connection.begin().await.map(PgUnitOfWork::new),
+        };
+
+        match unit_of_work {
+            Ok(unit_of_work) => Ok(Arc::new(PgStore {
+                connection: self.connection.clone(),
+                replica: self.replica.clone(),
+                reputation_rules: self.reputation_rules.clone(),
+                report_threshold: self.report_threshold,
+                unit_of_work: Some(Arc::new(unit_of_work)),
+            })),
+            Err(e) => Err(query_error(e)),
+        }
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for PgStore {
+    async fn commit(&self) -> Result<(), Error> {
+        let committed = match &self.unit_of_work {
+            Some(unit_of_work) => unit_of_work.commit().await,
+            None => Err(sqlx::Error::PoolClosed),
+        };
+
+        committed.map_err(query_error)
+    }
+
+    async fn rollback(&self) -> Result<(), Error> {
+        let rolled_back = match &self.unit_of_work {
+            Some(unit_of_work) => unit_of_work.rollback().await,
+            None => Err(sqlx::Error::PoolClosed),
+        };
+
+        rolled_back.map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl QuestionRepository for PgStore {
+    async fn is_question_owner(
+        &self,
+        question_id: i32,
+        account_id: &AccountId,
+    ) -> Result<bool, Error> {
+        let mut connection = self.acquire().await?;
+        match sqlx::query_scalar!(
+            "SELECT account_id from questions where id = $1 FOR UPDATE",
+            question_id
+        )
+        .fetch_optional(&mut *connection)
+        .await
+        {
+            Ok(Some(owner)) => Ok(owner == account_id.0),
+            Ok(None) => Err(Error::NotFound),
+            Err(e) => Err(query_error(e)),
+        }
+    }
+
+    async fn add_question(
+        &self,
+        new_question: NewQuestion,
+        account_id: AccountId,
+        flagged: bool,
+    ) -> Result<Question, Error> {
+        let mut connection = self.acquire().await?;
+        let added = async {
+            let mut tx = connection.begin().await?;
+
+            let question = sqlx::query_as!(
+                QuestionRow,
+                "INSERT INTO questions (title, content, tags, account_id, moderation_status)
+                VALUES ($1, $2, $3, $4, CASE WHEN $5 THEN 'pending' ELSE 'approved' END)
+                RETURNING id, title, content, tags, account_id,
+                (SELECT display_name FROM accounts WHERE accounts.id = questions.account_id)
+                AS display_name, created_on, updated_on, moderation_status, version",
+                new_question.title,
+                new_question.content,
+                new_question.tags.as_deref(),
+                account_id.0,
+                flagged
+            )
+            .fetch_one(&mut tx)
+            .await?;
+
+            self.refresh_reputation(&mut tx, &[account_id.0]).await?;
+            tx.commit().await?;
+            Ok::<_, sqlx::Error>(question.into())
+        }
+        .await;
+
+        match added {
+            Ok(question) => Ok(question),
+            Err(error) => Err(query_error(error)),
+        }
+    }
+
+    async fn get_questions(&self, limit: Option<i32>, offset: i32) -> Result<Vec<Question>, Error> {
+        let questions = self
+            .read(|connection| {
+                // Postgres does not report the nullability of columns under a
+                // `LIMIT`, so the ones which are never NULL are marked with `!`
+                Box::pin(sqlx::query_as!(
+                    QuestionRow,
+                    r#"SELECT questions.id AS "id!", title AS "title!", content AS "content!", tags,
+                    account_id AS "account_id!", accounts.display_name,
+                    questions.created_on AS "created_on!", updated_on AS "updated_on!",
+                    moderation_status AS "moderation_status!", version AS "version!" FROM questions
+                LEFT JOIN accounts ON accounts.id = questions.account_id
+                WHERE questions.deleted_at IS NULL AND questions.moderation_status = 'approved'
+                LIMIT $1 OFFSET $2"#,
+                    limit.map(i64::from),
+                    i64::from(offset)
+                )
+                .fetch_all(connection))
+            })
+            .await?;
+
+        Ok(questions.into_iter().map(Question::from).collect())
+    }
+
+    async fn get_question_by_id(&self, question_id: i32) -> Result<Question, Error> {
+        let question = self
+            .read(|connection| Box::pin(question_by_id(connection, question_id)))
+            .await?;
+
+        Ok(question.into())
+    }
+
+    async fn get_question_by_id_primary(&self, question_id: i32) -> Result<Question, Error> {
+        let mut connection = self.acquire().await?;
+        match question_by_id(&mut connection, question_id).await {
+            Ok(question) => Ok(question.into()),
+            Err(e) => Err(query_error(e)),
+        }
+    }
+
+    async fn find_question(
+        &self,
+        account_id: &AccountId,
+        title: &str,
+    ) -> Result<Option<Question>, Error> {
+        let mut connection = self.acquire().await?;
+        match sqlx::query_as!(
+            QuestionRow,
+            "SELECT questions.id, title, content, tags, account_id, accounts.display_name,
+            questions.created_on, updated_on, moderation_status, version FROM questions
+        LEFT JOIN accounts ON accounts.id = questions.account_id
+        WHERE questions.account_id = $1 AND questions.title = $2
+        AND questions.deleted_at IS NULL
+        ORDER BY questions.id",
+            account_id.0,
+            title
+        )
+        .fetch_optional(&mut *connection)
+        .await
+        {
+            Ok(question) => Ok(question.map(Question::from)),
+            Err(e) => Err(query_error(e)),
+        }
+    }
+
+    async fn update_question(
+        &self,
+        question: NewQuestion,
+        id: i32,
+        expected_versions: Option<Vec<i32>>,
+        flagged: bool,
+    ) -> Result<Question, Error> {
+        let mut connection = self.acquire().await?;
+        match sqlx::query_as!(
+            QuestionRow,
+            "UPDATE questions SET title = $1, content = $2, tags = $3,
+            version = version + 1, updated_on = NOW(),
+            moderation_status = CASE WHEN $6 THEN 'pending' ELSE moderation_status END
+            WHERE id = $4 AND deleted_at IS NULL
+            AND ($5::int[] IS NULL OR version = ANY($5))
+            RETURNING id, title, content, tags, account_id,
+            (SELECT display_name FROM accounts WHERE accounts.id = questions.account_id)
+            AS display_name, created_on, updated_on, moderation_status, version",
+            question.title,
+            question.content,
+            question.tags.as_deref(),
+            id,
+            expected_versions.as_deref(),
+            flagged
+        )
+        .fetch_optional(&mut *connection)
+        .await
+        {
+            Ok(Some(question)) => Ok(question.into()),
+            Ok(None) => Err(conditional_write_error(&mut connection, id).await),
+            Err(error) => Err(query_error(error)),
+        }
+    }
+
+    async fn patch_question(
+        &self,
+        patch: QuestionPatch,
+        id: i32,
+        expected_versions: Option<Vec<i32>>,
+        flagged: bool,
+    ) -> Result<Question, Error> {
+        let mut connection = self.acquire().await?;
+        match sqlx::query_as!(
+            QuestionRow,
+            "UPDATE questions SET title = COALESCE($1, title), content = COALESCE($2, content),
+            tags = CASE WHEN $7 THEN $3 ELSE tags END, version = version + 1, updated_on = NOW(),
+            moderation_status = CASE WHEN $6 THEN 'pending' ELSE moderation_status END
+            WHERE id = $4 AND deleted_at IS NULL
+            AND ($5::int[] IS NULL OR version = ANY($5))
+            RETURNING id, title, content, tags, account_id,
+            (SELECT display_name FROM accounts WHERE accounts.id = questions.account_id)
+            AS display_name, created_on, updated_on, moderation_status, version",
+            patch.title,
+            patch.content,
+            patch.tags.as_ref().and_then(Option::as_deref),
+            id,
+            expected_versions.as_deref(),
+            flagged,
+            patch.tags.is_some()
+        )
+        .fetch_optional(&mut *connection)
+        .await
+        {
+            Ok(Some(question)) => Ok(question.into()),
+            Ok(None) => Err(conditional_write_error(&mut connection, id).await),
+            Err(error) => Err(query_error(error)),
+        }
+    }
+
+    async fn delete_question(
+        &self,
+        id: i32,
+        account_id: AccountId,
+        expected_versions: Option<Vec<i32>>,
+    ) -> Result<bool, Error> {
+        let mut connection = self.acquire().await?;
+        let deleted = async {
+            let mut tx = connection.begin().await?;
+
+            let deleted = sqlx::query!(
+                "UPDATE questions SET deleted_at = NOW()
+            WHERE id = $1 AND account_id = $2 AND deleted_at IS NULL
+            AND ($3::int[] IS NULL OR version = ANY($3))",
+                id,
+                account_id.0,
+                expected_versions.as_deref()
+            )
+            .execute(&mut tx)
+            .await?
+            .rows_affected()
+                > 0;
+
+            if deleted {
+                self.refresh_question_reputation(&mut tx, id).await?;
+            }
+            tx.commit().await?;
+            Ok::<_, sqlx::Error>(deleted)
+        }
+        .await;
+
+        match deleted {
+            Ok(true) => Ok(true),
+            Ok(false) => match conditional_write_error(&mut connection, id).await {
+                Error::NotFound => Ok(false),
+                error => Err(error),
+            },
+            Err(e) => Err(query_error(e)),
+        }
+    }
+
+    async fn restore_question(&self, id: i32, account_id: AccountId) -> Result<Question, Error> {
+        let mut connection = self.acquire().await?;
+        let restored = async {
+            let mut tx = connection.begin().await?;
+
+            let question = sqlx::query_as!(
+                QuestionRow,
+                "UPDATE questions SET deleted_at = NULL
+                WHERE id = $1 AND account_id = $2 AND deleted_at IS NOT NULL
+                RETURNING id, title, content, tags, account_id,
+                (SELECT display_name FROM accounts WHERE accounts.id = questions.account_id)
+                AS display_name, created_on, updated_on, moderation_status, version",
+                id,
+                account_id.0
+            )
+            .fetch_one(&mut tx)
+            .await?;
+
+            self.refresh_question_reputation(&mut tx, id).await?;
+            tx.commit().await?;
+            Ok::<_, sqlx::Error>(question.into())
+        }
+        .await;
+
+        match restored {
+            Ok(question) => Ok(question),
+            Err(e) => Err(query_error(e)),
+        }
+    }
+
+    /// Answers of the purged questions are removed by the
+    /// `ON DELETE CASCADE` on `answers.corresponding_question`.
+    async fn purge_deleted_questions(&self, retention_days: i32) -> Result<u64, Error> {
+        let mut connection = self.acquire().await?;
+        let purged = async {
+            let mut tx = connection.begin().await?;
+
+            // Authors of the removed answers lose the points for them
+            let answerers = sqlx::query_scalar!(
+                "SELECT DISTINCT answers.account_id FROM answers
+            JOIN questions ON questions.id = answers.corresponding_question
+            WHERE questions.deleted_at < NOW() - make_interval(days => $1)",
+                retention_days
+            )
+            .fetch_all(&mut tx)
+            .await?;
+
+            let purged = sqlx::query!(
+                "DELETE FROM questions WHERE deleted_at < NOW() - make_interval(days => $1)",
+                retention_days
+            )
+            .execute(&mut tx)
+            .await?;
+
+            self.refresh_reputation(&mut tx, &answerers).await?;
+            tx.commit().await?;
+            Ok::<_, sqlx::Error>(purged.rows_affected())
+        }
+        .await;
+
+        match purged {
+            Ok(purged) => Ok(purged),
+            Err(e) => Err(query_error(e)),
+        }
+    }
+
+    async fn add_answer(
+        &self,
+        answer: Answer,
+        account_id: AccountId,
+        flagged: bool,
+    ) -> Result<bool, Error> {
+        let mut connection = self.acquire().await?;
+        let added = async {
+            let mut tx = connection.begin().await?;
+
+            // Deleted questions take no answers, they would never be shown
+            let inserted = sqlx::query!(
+                "INSERT INTO answers (content, corresponding_question, account_id, moderation_status)
+                SELECT $1, id, $3, CASE WHEN $4 THEN 'pending' ELSE 'approved' END
+                FROM questions WHERE id = $2 AND deleted_at IS NULL",
+                answer.content,
+                answer.question_id,
+                account_id.0,
+                flagged
+            )
+            .execute(&mut tx)
+            .await?
+            .rows_affected();
+            if inserted == 0 {
+                return Ok(false);
+            }
+
+            self.refresh_reputation(&mut tx, &[account_id.0]).await?;
+            tx.commit().await?;
+            Ok(true)
+        }
+        .await;
+
+        match added {
+            Ok(true) => Ok(true),
+            Ok(false) => Err(Error::NotFound),
+            Err(error) => Err(query_error(error)),
+        }
+    }
+
+    async fn answer_exists(
+        &self,
+        question_id: i32,
+        account_id: &AccountId,
+        content: &str,
+    ) -> Result<bool, Error> {
+        let mut connection = self.acquire().await?;
+        match sqlx::query_scalar!(
+            r#"SELECT EXISTS (SELECT 1 FROM answers WHERE corresponding_question = $1
+            AND account_id = $2 AND content = $3) AS "exists!""#,
+            question_id,
+            account_id.0,
+            content
+        )
+        .fetch_one(&mut *connection)
+        .await
+        {
+            Ok(exists) => Ok(exists),
+            Err(e) => Err(query_error(e)),
+        }
+    }
+}
+
+#[async_trait]
+impl ModerationRepository for PgStore {
+    async fn get_moderation_queue(&self) -> Result<ModeratedPosts, Error> {
+        let mut connection = self.acquire().await?;
+        // One after the other on the same connection, which is the one
+        // of the unit of work if there is one
+        let posts = async {
+            let questions = sqlx::query_as!(
+                QuestionRow,
+                "SELECT questions.id, title, content, tags, account_id, accounts.display_name,
+            questions.created_on, updated_on, moderation_status, version FROM questions
+        LEFT JOIN accounts ON accounts.id = questions.account_id
+        WHERE questions.moderation_status = 'pending' AND questions.deleted_at IS NULL
+        ORDER BY questions.created_on"
+            )
+            .fetch_all(&mut *connection)
+            .await?;
+
+            let answers = sqlx::query_as!(
+                AnswerRow,
+                r#"SELECT answers.id, answers.content,
+            answers.corresponding_question AS "corresponding_question!", answers.account_id,
+            answers.created_on, answers.moderation_status FROM answers
+        JOIN questions ON questions.id = answers.corresponding_question
+        WHERE answers.moderation_status = 'pending' AND questions.deleted_at IS NULL
+        ORDER BY answers.created_on"#
+            )
+            .fetch_all(&mut *connection)
+            .await?;
+            Ok::<_, sqlx::Error>((questions, answers))
+        }
+        .await;
+
+        match posts {
+            Ok((questions, answers)) => Ok(moderated_posts(questions, answers)),
+            Err(e) => Err(query_error(e)),
+        }
+    }
+
+    async fn get_unapproved_posts(&self, account_id: &AccountId) -> Result<ModeratedPosts, Error> {
+        let mut connection = self.acquire().await?;
+        // One after the other on the same connection, which is the one
+        // of the unit of work if there is one
+        let posts = async {
+            let questions = sqlx::query_as!(
+                QuestionRow,
+                "SELECT questions.id, title, content, tags, account_id, accounts.display_name,
+            questions.created_on, updated_on, moderation_status, version FROM questions
+        LEFT JOIN accounts ON accounts.id = questions.account_id
+        WHERE questions.account_id = $1 AND questions.moderation_status <> 'approved'
+        AND questions.deleted_at IS NULL
+        ORDER BY questions.created_on DESC",
+                account_id.0
+            )
+            .fetch_all(&mut *connection)
+            .await?;
+
+            let answers = sqlx::query_as!(
+                AnswerRow,
+                r#"SELECT answers.id, answers.content,
+            answers.corresponding_question AS "corresponding_question!", answers.account_id,
+            answers.created_on, answers.moderation_status FROM answers
+        JOIN questions ON questions.id = answers.corresponding_question
+        WHERE answers.account_id = $1 AND answers.moderation_status <> 'approved'
+        AND questions.deleted_at IS NULL
+        ORDER BY answers.created_on DESC"#,
+                account_id.0
+            )
+            .fetch_all(&mut *connection)
+            .await?;
+            Ok::<_, sqlx::Error>((questions, answers))
+        }
+        .await;
+
+        match posts {
+            Ok((questions, answers)) => Ok(moderated_posts(questions, answers)),
+            Err(e) => Err(query_error(e)),
+        }
+    }
+
+    async fn set_question_status(
+        &self,
+        id: i32,
+        status: ModerationStatus,
+    ) -> Result<Question, Error> {
+        let mut connection = self.acquire().await?;
+        let updated = async {
+            let mut tx = connection.begin().await?;
+
+            let question = sqlx::query_as!(
+                QuestionRow,
+                "UPDATE questions SET moderation_status = $1
+                WHERE id = $2 AND deleted_at IS NULL
+                RETURNING id, title, content, tags, account_id,
+                (SELECT display_name FROM accounts WHERE accounts.id = questions.account_id)
+                AS display_name, created_on, updated_on, moderation_status, version",
+                status.as_str(),
+                id
+            )
+            .fetch_optional(&mut tx)
+            .await?;
+
+            if question.is_some() {
+                self.refresh_question_reputation(&mut tx, id).await?;
+            }
+            tx.commit().await?;
+            Ok::<_, sqlx::Error>(question)
+        }
+        .await;
+
+        match updated {
+            Ok(Some(question)) => Ok(question.into()),
+            Ok(None) => Err(Error::NotFound),
+            Err(e) => Err(query_error(e)),
+        }
+    }
+
+    async fn set_answer_status(
+        &self,
+        id: i32,
+        status: ModerationStatus,
+    ) -> Result<PostedAnswer, Error> {
+        let mut connection = self.acquire().await?;
+        let updated = async {
+            let mut tx = connection.begin().await?;
+
+            let answer = sqlx::query_as!(
+                AnswerRow,
+                r#"UPDATE answers SET moderation_status = $1 WHERE id = $2
+                RETURNING id, content, corresponding_question AS "corresponding_question!",
+                account_id, created_on, moderation_status"#,
+                status.as_str(),
+                id
+            )
+            .fetch_optional(&mut tx)
+            .await?;
+
+            if let Some(answer) = &answer {
+                self.refresh_reputation(&mut tx, &[answer.account_id])
+                    .await?;
+            }
+            tx.commit().await?;
+            Ok::<_, sqlx::Error>(answer)
+        }
+        .await;
+
+        match updated {
+            Ok(Some(answer)) => Ok(answer.into()),
+            Ok(None) => Err(Error::NotFound),
+            Err(e) => Err(query_error(e)),
+        }
+    }
+
+    async fn add_report(
+        &self,
+        target: ReportTarget,
+        reporter: AccountId,
+        report: NewReport,
+    ) -> Result<bool, Error> {
+        let mut connection = self.acquire().await?;
+        let (question_id, answer_id) = report_columns(target);
+
+        let added = async {
+            let mut tx = connection.begin().await?;
+
+            // Locks the post, so concurrent reports are counted one after
+            // another and none of them misses the threshold
+            let author = match target {
+                ReportTarget::Question(id) => {
+                    sqlx::query_scalar!(
+                        "SELECT account_id FROM questions
+                WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+                        id
+                    )
+                    .fetch_optional(&mut tx)
+                    .await?
+                }
+                ReportTarget::Answer(id) => {
+                    sqlx::query_scalar!(
+                        "SELECT answers.account_id FROM answers
+                JOIN questions ON questions.id = answers.corresponding_question
+                WHERE answers.id = $1 AND questions.deleted_at IS NULL
+                FOR UPDATE OF answers",
+                        id
+                    )
+                    .fetch_optional(&mut tx)
+                    .await?
+                }
+            };
+            match author {
+                None => return Ok(Err(Error::NotFound)),
+                Some(author) if author == reporter.0 => {
+                    return Ok(Err(Error::InvalidInput(
+                        "authors cannot report their own posts".to_string(),
+                    )))
+                }
+                Some(_) => {}
+            }
+
+            let created = sqlx::query!(
+                "INSERT INTO reports (reporter_id, question_id, answer_id, reason, note)
+                VALUES ($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING",
+                reporter.0,
+                question_id,
+                answer_id,
+                report.reason.as_str(),
+                report.note
+            )
+            .execute(&mut tx)
+            .await?
+            .rows_affected()
+                > 0;
+
+            if created {
+                let open = sqlx::query_scalar!(
+                    r#"SELECT COUNT(*) AS "open!" FROM reports
+                    WHERE (question_id = $1 OR answer_id = $2) AND resolved_on IS NULL"#,
+                    question_id,
+                    answer_id
+                )
+                .fetch_one(&mut tx)
+                .await?;
+
+                if open >= self.report_threshold.0 {
+                    self.update_post_status(
+                        &mut tx,
+                        target,
+                        ModerationStatus::Pending,
+                        Some(ModerationStatus::Approved),
+                    )
+                    .await?;
+                }
+            }
+            tx.commit().await?;
+            Ok::<_, sqlx::Error>(Ok(created))
+        }
+        .await;
+
+        match added {
+            Ok(added) => added,
+            Err(e) => Err(query_error(e)),
+        }
+    }
+
+    async fn get_open_reports(&self) -> Result<Vec<Report>, Error> {
+        let mut connection = self.acquire().await?;
+        match sqlx::query_as!(
+            ReportRow,
+            "SELECT id, reporter_id, question_id, answer_id, reason, note,
+            created_on, resolved_on, resolution
+            FROM reports WHERE resolved_on IS NULL ORDER BY created_on"
+        )
+        .fetch_all(&mut *connection)
+        .await
+        {
+            Ok(reports) => Ok(reports.into_iter().map(Report::from).collect()),
+            Err(e) => Err(query_error(e)),
+        }
+    }
+
+    async fn resolve_report(
+        &self,
+        id: i32,
+        action: ReportAction,
+        moderator: AccountId,
+    ) -> Result<Vec<Report>, Error> {
+        let mut connection = self.acquire().await?;
+        let resolved = async {
+            let mut tx = connection.begin().await?;
+
+            let target = sqlx::query!(
+                "SELECT question_id, answer_id FROM reports
+            WHERE id = $1 AND resolved_on IS NULL FOR UPDATE",
+                id
+            )
+            .fetch_optional(&mut tx)
+            .await?
+            .and_then(|report| {
+                report
+                    .question_id
+                    .map(ReportTarget::Question)
+                    .or_else(|| report.answer_id.map(ReportTarget::Answer))
+            });
+
+            let Some(target) = target else {
+                return Ok(None);
+            };
+
+            self.update_post_status(&mut tx, target, action.status(), None)
+                .await?;
+
+            let reports = close_reports(&mut tx, target, action, &moderator).await?;
+
+            tx.commit().await?;
+            Ok::<_, sqlx::Error>(Some(reports))
+        }
+        .await;
+
+        match resolved {
+            Ok(Some(reports)) => Ok(reports),
+            Ok(None) => Err(Error::NotFound),
+            Err(e) => Err(query_error(e)),
+        }
+    }
+
+    async fn resolve_reports(
+        &self,
+        target: ReportTarget,
+        action: ReportAction,
+        moderator: AccountId,
+    ) -> Result<Vec<Report>, Error> {
+        let mut connection = self.acquire().await?;
+        let resolved = async {
+            let mut tx = connection.begin().await?;
+            let reports = close_reports(&mut tx, target, action, &moderator).await?;
+            tx.commit().await?;
+            Ok::<_, sqlx::Error>(reports)
+        }
+        .await;
+
+        resolved.map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl AccountRepository for PgStore {
+    async fn add_account(&self, account: Account) -> Result<bool, Error> {
+        let mut connection = self.acquire().await?;
+        match sqlx::query!(
+            "INSERT INTO accounts (email, password) VALUES ($1, $2)",
+            account.email,
+            account.password
+        )
+        .execute(&mut *connection)
+        .await
+        {
+            Ok(_) => Ok(true),
+            Err(error) => Err(query_error(error)),
+        }
+    }
+
+    async fn get_account(&self, email: String) -> Result<Credentials, Error> {
+        let mut connection = self.acquire().await?;
+        match sqlx::query_as!(
+            AccountRow,
+            "SELECT id, email, password from accounts where lower(email) = lower($1)",
+            email
+        )
+        .fetch_one(&mut *connection)
+        .await
+        {
+            Ok(account) => Ok(account.into()),
+            Err(error) => Err(query_error(error)),
+        }
+    }
+
+    async fn get_profile(&self, account_id: i32) -> Result<Profile, Error> {
+        let mut connection = self.acquire().await?;
+        let account = match sqlx::query_as!(
+            ProfileRow,
+            "SELECT id, display_name, bio, avatar_url, created_on FROM accounts WHERE id = $1",
+            account_id
+        )
+        .fetch_one(&mut *connection)
+        .await
+        {
+            Ok(account) => account,
+            Err(error) => return Err(query_error(error)),
+        };
+
+        // One after the other on the same connection, which is the one
+        // of the unit of work if there is one
+        let posts = async {
+            let questions = sqlx::query_as!(
+                QuestionRow,
+                "SELECT questions.id, title, content, tags, account_id, accounts.display_name,
+            questions.created_on, updated_on, moderation_status, version FROM questions
+        LEFT JOIN accounts ON accounts.id = questions.account_id
+        WHERE questions.account_id = $1 AND questions.deleted_at IS NULL
+        AND questions.moderation_status = 'approved'
+        ORDER BY questions.created_on DESC",
+                account_id
+            )
+            .fetch_all(&mut *connection)
+            .await?;
+
+            let answers = sqlx::query_as!(
+                AnswerRow,
+                r#"SELECT answers.id, answers.content,
+            answers.corresponding_question AS "corresponding_question!", answers.account_id,
+            answers.created_on, answers.moderation_status FROM answers
+        JOIN questions ON questions.id = answers.corresponding_question
+        WHERE answers.account_id = $1 AND questions.deleted_at IS NULL
+        AND answers.moderation_status = 'approved' AND questions.moderation_status = 'approved'
+        ORDER BY answers.created_on DESC"#,
+                account_id
+            )
+            .fetch_all(&mut *connection)
+            .await?;
+            Ok::<_, sqlx::Error>((questions, answers))
+        }
+        .await;
+
+        match posts {
+            Ok((questions, answers)) => Ok(account.into_profile(
+                questions.into_iter().map(Question::from).collect(),
+                answers.into_iter().map(PostedAnswer::from).collect(),
+            )),
+            Err(error) => Err(query_error(error)),
+        }
+    }
+
+    async fn update_profile(
+        &self,
+        account_id: AccountId,
+        profile: ProfileUpdate,
+    ) -> Result<bool, Error> {
+        let mut connection = self.acquire().await?;
+        match sqlx::query!(
+            "UPDATE accounts SET display_name = $1, bio = $2, avatar_url = $3 WHERE id = $4",
+            profile.display_name,
+            profile.bio,
+            profile.avatar_url,
+            account_id.0
+        )
+        .execute(&mut *connection)
+        .await
+        {
+            Ok(done) => Ok(done.rows_affected() > 0),
+            Err(error) => Err(query_error(error)),
+        }
+    }
+
+    async fn get_reputation(&self, account_id: i32) -> Result<i64, Error> {
+        let mut connection = self.acquire().await?;
+        match sqlx::query_scalar!("SELECT reputation FROM accounts WHERE id = $1", account_id)
+            .fetch_one(&mut *connection)
+            .await
+        {
+            Ok(reputation) => Ok(reputation),
+            Err(error) => Err(query_error(error)),
+        }
+    }
+
+    async fn is_moderator(&self, account_id: &AccountId) -> Result<bool, Error> {
+        let mut connection = self.acquire().await?;
+        match sqlx::query_scalar!(
+            "SELECT is_moderator FROM accounts WHERE id = $1",
+            account_id.0
+        )
+        .fetch_optional(&mut *connection)
+        .await
+        {
+            Ok(is_moderator) => Ok(is_moderator.unwrap_or(false)),
+            Err(e) => Err(query_error(e)),
+        }
+    }
+
+    async fn is_admin(&self, account_id: &AccountId) -> Result<bool, Error> {
+        let mut connection = self.acquire().await?;
+        match sqlx::query_scalar!("SELECT is_admin FROM accounts WHERE id = $1", account_id.0)
+            .fetch_optional(&mut *connection)
+            .await
+        {
+            Ok(is_admin) => Ok(is_admin.unwrap_or(false)),
+            Err(e) => Err(query_error(e)),
+        }
+    }
+}
+
+#[async_trait]
+impl TransferRepository for PgStore {
+    fn export(&self, with_passwords: bool) -> BoxStream<'static, Result<Record, Error>> {
+        // Reads from the pool, not from a unit of work
+        let export = Export {
+            pool: self.connection.clone(),
+            transaction: None,
+            table: Some(ExportTable::Accounts),
+            after: 0,
+            with_passwords,
+        };
+
+        stream::try_unfold(export, |mut export| async move {
+            match export.next_page().await {
+                Ok(Some(page)) => Ok(Some((stream::iter(page.into_iter().map(Ok)), export))),
+                Ok(None) => Ok(None),
+                Err(e) => Err(query_error(e)),
+            }
+        })
+        .try_flatten()
+        .boxed()
+    }
+
+    async fn import(&self, record: Record) -> Result<(), Error> {
+        let mut connection = self.acquire().await?;
+        let imported = match record {
+            Record::Account(account) => {
+                sqlx::query!(
+                    "INSERT INTO accounts (id, email, password, display_name, bio, avatar_url,
+                    is_moderator, is_admin, created_on)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+                    account.id.0,
+                    account.email,
+                    account.password,
+                    account.display_name,
+                    account.bio,
+                    account.avatar_url,
+                    account.is_moderator,
+                    account.is_admin,
+                    account.created_on
+                )
+                .execute(&mut *connection)
+                .await
+            }
+            Record::Question(question) => {
+                sqlx::query!(
+                    "INSERT INTO questions (id, account_id, title, content, tags,
+                    moderation_status, version, created_on, updated_on, deleted_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+                    question.id.0,
+                    question.account_id.0,
+                    question.title,
+                    question.content,
+                    question.tags.as_deref(),
+                    question.moderation_status.as_str(),
+                    question.version,
+                    question.created_on,
+                    question.updated_on,
+                    question.deleted_at
+                )
+                .execute(&mut *connection)
+                .await
+            }
+            Record::Answer(answer) => {
+                sqlx::query!(
+                    "INSERT INTO answers (id, corresponding_question, account_id, content,
+                    moderation_status, created_on) VALUES ($1, $2, $3, $4, $5, $6)",
+                    answer.id.0,
+                    answer.question_id.0,
+                    answer.account_id.0,
+                    answer.content,
+                    answer.moderation_status.as_str(),
+                    answer.created_on
+                )
+                .execute(&mut *connection)
+                .await
+            }
+        };
+
+        match imported {
+            Ok(_) => Ok(()),
+            Err(e) => Err(query_error(e)),
+        }
+    }
+
+    async fn finish_import(&self) -> Result<(), Error> {
+        let mut connection = self.acquire().await?;
+        let finished = async {
+            let mut tx = connection.begin().await?;
+
+            // Only moved forward, to where `nextval` hands out an id
+            // which is larger than all imported ones
+            sqlx::query!(
+                "SELECT setval('accounts_id_seq', MAX(id)) FROM accounts
+                HAVING MAX(id) >= (SELECT last_value + is_called::int FROM accounts_id_seq)"
+            )
+            .fetch_optional(&mut tx)
+            .await?;
+            sqlx::query!(
+                "SELECT setval('questions_id_seq', MAX(id)) FROM questions
+                HAVING MAX(id) >= (SELECT last_value + is_called::int FROM questions_id_seq)"
+            )
+            .fetch_optional(&mut tx)
+            .await?;
+            sqlx::query!(
+                "SELECT setval('answers_id_seq', MAX(id)) FROM answers
+                HAVING MAX(id) >= (SELECT last_value + is_called::int FROM answers_id_seq)"
+            )
+            .fetch_optional(&mut tx)
+            .await?;
+
+            let account_ids = sqlx::query_scalar!("SELECT id FROM accounts")
+                .fetch_all(&mut tx)
+                .await?;
+            self.refresh_reputation(&mut tx, &account_ids).await?;
+            tx.commit().await
+        }
+        .await;
+
+        match finished {
+            Ok(()) => Ok(()),
+            Err(e) => Err(query_error(e)),
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+
+use crate::types::{
+    account::{AccountId, Author, Credentials, Profile},
+    answer::{AnswerId, PostedAnswer},
+    export::{AccountRecord, AnswerRecord, QuestionRecord, Record},
+    moderation::{ModeratedPosts, ModerationStatus},
+    question::{Question, QuestionId},
+    report::{Report, ReportAction, ReportId, ReportReason},
+};
+
+/// A row of `questions` together with the display name of its author
+#[derive(Debug, FromRow)]
+pub struct QuestionRow {
+    pub id: i32,
+    pub title: String,
+    pub content: String,
+    pub tags: Option<Vec<String>>,
+    pub account_id: i32,
+    pub display_name: Option<String>,
+    pub created_on: DateTime<Utc>,
+    pub updated_on: DateTime<Utc>,
+    pub moderation_status: String,
+    pub version: i32,
+}
+
+impl From<QuestionRow> for Question {
+    fn from(row: QuestionRow) -> Self {
+        Question {
+            id: QuestionId(row.id),
+            title: row.title,
+            content: row.content,
+            tags: row.tags,
+            author: Author {
+                id: AccountId(row.account_id),
+                display_name: row.display_name,
+            },
+            created_on: row.created_on,
+            updated_on: row.updated_on,
+            moderation_status: ModerationStatus::from_db(&row.moderation_status),
+            version: row.version,
+        }
+    }
+}
+
+/// A row of `answers`
+#[derive(Debug, FromRow)]
+pub struct AnswerRow {
+    pub id: i32,
+    pub content: String,
+    pub corresponding_question: i32,
+    pub account_id: i32,
+    pub created_on: DateTime<Utc>,
+    pub moderation_status: String,
+}
+
+impl From<AnswerRow> for PostedAnswer {
+    fn from(row: AnswerRow) -> Self {
+        PostedAnswer {
+            id: AnswerId(row.id),
+            content: row.content,
+            question_id: QuestionId(row.corresponding_question),
+            created_on: row.created_on,
+            moderation_status: ModerationStatus::from_db(&row.moderation_status),
+        }
+    }
+}
+
+pub fn moderated_posts(questions: Vec<QuestionRow>, answers: Vec<AnswerRow>) -> ModeratedPosts {
+    ModeratedPosts {
+        questions: questions.into_iter().map(Question::from).collect(),
+        answers: answers.into_iter().map(PostedAnswer::from).collect(),
+    }
+}
+
+/// A row of `reports`
+#[derive(Debug, FromRow)]
+pub struct ReportRow {
+    pub id: i32,
+    pub reporter_id: i32,
+    pub question_id: Option<i32>,
+    pub answer_id: Option<i32>,
+    pub reason: String,
+    pub note: Option<String>,
+    pub created_on: DateTime<Utc>,
+    pub resolved_on: Option<DateTime<Utc>>,
+    pub resolution: Option<String>,
+}
+
+impl From<ReportRow> for Report {
+    fn from(row: ReportRow) -> Self {
+        Report {
+            id: ReportId(row.id),
+            reporter: AccountId(row.reporter_id),
+            question_id: row.question_id.map(QuestionId),
+            answer_id: row.answer_id.map(AnswerId),
+            reason: ReportReason::from_db(&row.reason),
+            note: row.note,
+            created_on: row.created_on,
+            resolved_on: row.resolved_on,
+            resolution: row.resolution.as_deref().and_then(ReportAction::from_db),
+        }
+    }
+}
+
+/// The credentials of an account
+#[derive(Debug, FromRow)]
+pub struct AccountRow {
+    pub id: i32,
+    pub email: String,
+    pub password: Option<String>,
+}
+
+impl From<AccountRow> for Credentials {
+    fn from(row: AccountRow) -> Self {
+        Credentials {
+            id: AccountId(row.id),
+            email: row.email,
+            password: row.password,
+        }
+    }
+}
+
+/// The public columns of an account
+#[derive(Debug, FromRow)]
+pub struct ProfileRow {
+    pub id: i32,
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub avatar_url: Option<String>,
+    pub created_on: DateTime<Utc>,
+}
+
+impl ProfileRow {
+    /// The profile with the approved posts of the account
+    pub fn into_profile(self, questions: Vec<Question>, answers: Vec<PostedAnswer>) -> Profile {
+        Profile {
+            id: AccountId(self.id),
+            display_name: self.display_name,
+            bio: self.bio,
+            avatar_url: self.avatar_url,
+            joined_on: self.created_on,
+            questions,
+            answers,
+        }
+    }
+}
+
+/// A row of `accounts` as it is exported
+#[derive(Debug, FromRow)]
+pub struct AccountExportRow {
+    pub id: i32,
+    pub email: String,
+    pub password: Option<String>,
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub avatar_url: Option<String>,
+    pub is_moderator: bool,
+    pub is_admin: bool,
+    pub created_on: DateTime<Utc>,
+}
+
+impl From<AccountExportRow> for Record {
+    fn from(row: AccountExportRow) -> Self {
+        Record::Account(AccountRecord {
+            id: AccountId(row.id),
+            email: row.email,
+            password: row.password,
+            display_name: row.display_name,
+            bio: row.bio,
+            avatar_url: row.avatar_url,
+            is_moderator: row.is_moderator,
+            is_admin: row.is_admin,
+            created_on: row.created_on,
+        })
+    }
+}
+
+/// A row of `questions` as it is exported, deleted ones included
+#[derive(Debug, FromRow)]
+pub struct QuestionExportRow {
+    pub id: i32,
+    pub account_id: i32,
+    pub title: String,
+    pub content: String,
+    pub tags: Option<Vec<String>>,
+    pub moderation_status: String,
+    pub version: i32,
+    pub created_on: DateTime<Utc>,
+    pub updated_on: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl From<QuestionExportRow> for Record {
+    fn from(row: QuestionExportRow) -> Self {
+        Record::Question(QuestionRecord {
+            id: QuestionId(row.id),
+            account_id: AccountId(row.account_id),
+            title: row.title,
+            content: row.content,
+            tags: row.tags,
+            moderation_status: ModerationStatus::from_db(&row.moderation_status),
+            version: row.version,
+            created_on: row.created_on,
+            updated_on: row.updated_on,
+            deleted_at: row.deleted_at,
+        })
+    }
+}
+
+impl From<AnswerRow> for Record {
+    fn from(row: AnswerRow) -> Self {
+        Record::Answer(AnswerRecord {
+            id: AnswerId(row.id),
+            question_id: QuestionId(row.corresponding_question),
+            account_id: AccountId(row.account_id),
+            content: row.content,
+            moderation_status: ModerationStatus::from_db(&row.moderation_status),
+            created_on: row.created_on,
+        })
+    }
+}
@@ -0,0 +1,284 @@
+use futures::stream::{Stream, StreamExt};
+use std::{collections::HashSet, io};
+use tracing::{event, Level};
+
+use handle_errors::Error;
+
+use crate::store::Store;
+use crate::types::export::{Imported, Record};
+
+/// Every record of the store as one line of JSON, see
+/// `TransferRepository::export`
+#[allow(clippy::missing_panics_doc)] // records always serialize to JSON
+pub fn export_lines(
+    store: &Store,
+    with_passwords: bool,
+) -> impl Stream<Item = Result<String, Error>> + Send + 'static {
+    store.export(with_passwords).map(|record| {
+        let mut line = serde_json::to_string(&record?).expect("records serialize to JSON");
+        line.push('\n');
+        Ok(line)
+    })
+}
+
+/// Stores the records of an export, all of them or none. The lines are
+/// parsed and stored one at a time, so they never all are in memory.
+/// Empty lines are skipped. Questions and answers must only refer to
+/// accounts and questions of the same import, so an import cannot add
+/// posts to the content of the store. Accounts which can log in,
+/// moderate or administer are only stored `with_privileges`.
+///
+/// # Errors
+///
+/// With `InvalidInput` naming the line if a line is no record, refers to
+/// a record the import did not bring or is a privileged account which is
+/// not allowed, or with the error of the store if a record cannot be
+/// stored
+pub async fn import<S>(store: &Store, lines: S, with_privileges: bool) -> Result<Imported, Error>
+where
+    S: Stream<Item = io::Result<String>> + Send + Unpin + 'static,
+{
+    store
+        .transaction(|store| async move {
+            let mut imported = Imported::default();
+            let mut accounts = HashSet::new();
+            let mut questions = HashSet::new();
+            let mut lines = lines.enumerate();
+
+            while let Some((index, line)) = lines.next().await {
+                let number = index + 1;
+                let line = line.map_err(|e| Error::InvalidInput(format!("line {number}: {e}")))?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let record: Record = serde_json::from_str(&line)
+                    .map_err(|e| Error::InvalidInput(format!("line {number}: {e}")))?;
+                let not_imported = |what: &str, id: i32| {
+                    Error::InvalidInput(format!(
+                        "line {number}: {what} {id} is not part of the import"
+                    ))
+                };
+                match &record {
+                    Record::Account(account) => {
+                        if !with_privileges
+                            && (account.password.is_some()
+                                || account.is_moderator
+                                || account.is_admin)
+                        {
+                            return Err(Error::InvalidInput(format!(
+                                "line {number}: account {} can log in, moderate or administer",
+                                account.id.0
+                            )));
+                        }
+                        accounts.insert(account.id.clone());
+                        imported.accounts += 1;
+                    }
+                    Record::Question(question) => {
+                        if !accounts.contains(&question.account_id) {
+                            return Err(not_imported("account", question.account_id.0));
+                        }
+                        questions.insert(question.id.clone());
+                        imported.questions += 1;
+                    }
+                    Record::Answer(answer) => {
+                        if !accounts.contains(&answer.account_id) {
+                            return Err(not_imported("account", answer.account_id.0));
+                        }
+                        if !questions.contains(&answer.question_id) {
+                            return Err(not_imported("question", answer.question_id.0));
+                        }
+                        imported.answers += 1;
+                    }
+                }
+
+                if let Err(e) = store.import(record).await {
+                    event!(
+                        target: "warp-rest-api",
+                        Level::WARN,
+                        line = number,
+                        "Cannot import record: {}",
+                        e
+                    );
+                    return Err(e);
+                }
+            }
+
+            store.finish_import().await?;
+            Ok(imported)
+        })
+        .await
+}
+
+#[cfg(test)]
+mod transfer_tests {
+    use super::{export_lines, import};
+    use crate::store::{memory::MemoryStore, Store};
+    use crate::types::{
+        account::{Account, AccountId},
+        answer::Answer,
+        export::Imported,
+        question::NewQuestion,
+    };
+    use futures::{stream, StreamExt, TryStreamExt};
+    use handle_errors::Error;
+    use std::sync::Arc;
+
+    async fn lines(store: &Store, with_passwords: bool) -> Vec<String> {
+        export_lines(store, with_passwords)
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    fn source(lines: Vec<String>) -> impl futures::Stream<Item = std::io::Result<String>> {
+        stream::iter(
+            lines
+                .into_iter()
+                .map(|line| Ok(line.trim_end().to_string())),
+        )
+    }
+
+    #[tokio::test]
+    async fn export_and_import_keep_ids() {
+        let memory = Arc::new(MemoryStore::default());
+        let store = Store::new(memory.clone());
+        store
+            .add_account(Account {
+                id: None,
+                email: "spare@email.com".to_string(),
+                password: "hash".to_string(),
+            })
+            .await
+            .unwrap();
+        let author = memory.create_account("author@email.com");
+        let question = store
+            .add_question(
+                NewQuestion {
+                    title: "Title".to_string(),
+                    content: "Content".to_string(),
+                    tags: Some(vec!["tag".to_string()]),
+                },
+                author.clone(),
+                false,
+            )
+            .await
+            .unwrap();
+        store
+            .add_answer(
+                Answer {
+                    content: "Answer".to_string(),
+                    question_id: question.id.0,
+                },
+                author,
+                false,
+            )
+            .await
+            .unwrap();
+
+        let exported = lines(&store, false).await;
+        assert_eq!(exported.len(), 4);
+        assert!(!exported[0].contains("\"password\""));
+        assert!(lines(&store, true).await[0].contains(r#""password":"hash""#));
+        // The author has no password, so there is none to export
+        assert!(!lines(&store, true).await[1].contains("\"password\""));
+
+        let target = Store::new(Arc::new(MemoryStore::default()));
+        let imported = import(&target, source(exported.clone()), true)
+            .await
+            .unwrap();
+        assert_eq!(
+            imported,
+            Imported {
+                accounts: 2,
+                questions: 1,
+                answers: 1
+            }
+        );
+        assert_eq!(lines(&target, false).await, exported);
+
+        // New rows get ids after the imported ones
+        let next = target
+            .add_question(
+                NewQuestion {
+                    title: "Next".to_string(),
+                    content: "Content".to_string(),
+                    tags: None,
+                },
+                AccountId(2),
+                false,
+            )
+            .await
+            .unwrap();
+        assert_eq!(next.id.0, question.id.0 + 1);
+
+        // The ids are taken now, so nothing of a second import is stored
+        assert!(matches!(
+            import(&target, source(exported), true).await,
+            Err(Error::UniqueViolation(_))
+        ));
+        assert_eq!(target.get_questions(None, 0).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn invalid_line_imports_nothing() {
+        let store = Store::new(Arc::new(MemoryStore::default()));
+        let lines = vec![
+            r#"{"type":"account","id":1,"email":"a@email.com","display_name":null,"bio":null,"avatar_url":null,"is_moderator":false,"created_on":"2023-03-01T00:00:00Z"}"#.to_string(),
+            String::new(),
+            r#"{"type":"question","id":1}"#.to_string(),
+        ];
+
+        match import(&store, source(lines), true).await {
+            Err(Error::InvalidInput(reason)) => assert!(reason.starts_with("line 3:")),
+            other => panic!("expected invalid input, got {other:?}"),
+        }
+        assert!(store.export(false).next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn refuse_foreign_and_privileged_records() {
+        let store = Store::new(Arc::new(MemoryStore::default()));
+        let account = |id: i32, is_moderator: bool| {
+            format!(
+                r#"{{"type":"account","id":{id},"email":"{id}@email.com","display_name":null,"bio":null,"avatar_url":null,"is_moderator":{is_moderator},"created_on":"2023-03-01T00:00:00Z"}}"#
+            )
+        };
+        let question = r#"{"type":"question","id":1,"account_id":1,"title":"Title","content":"Content","tags":null,"moderation_status":"approved","version":1,"created_on":"2023-03-01T00:00:00Z","updated_on":"2023-03-01T00:00:00Z","deleted_at":null}"#;
+        let answer = r#"{"type":"answer","id":1,"question_id":1,"account_id":1,"content":"Answer","moderation_status":"approved","created_on":"2023-03-01T00:00:00Z"}"#;
+
+        // The author is not part of the import
+        let lines = vec![account(2, false), question.to_string()];
+        match import(&store, source(lines), false).await {
+            Err(Error::InvalidInput(reason)) => {
+                assert_eq!(reason, "line 2: account 1 is not part of the import");
+            }
+            other => panic!("expected invalid input, got {other:?}"),
+        }
+
+        // Neither is the question
+        let lines = vec![account(1, false), answer.to_string()];
+        assert!(matches!(
+            import(&store, source(lines), false).await,
+            Err(Error::InvalidInput(_))
+        ));
+
+        // Moderators are only imported with privileges
+        let lines = vec![account(1, true), question.to_string(), answer.to_string()];
+        assert!(matches!(
+            import(&store, source(lines.clone()), false).await,
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(store.export(false).next().await.is_none());
+
+        let imported = import(&store, source(lines), true).await.unwrap();
+        assert_eq!(
+            imported,
+            Imported {
+                accounts: 1,
+                questions: 1,
+                answers: 1
+            }
+        );
+    }
+}
@@ -1,6 +1,11 @@
 use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    profanity::TextFields,
+    types::{answer::PostedAnswer, question::Question},
+};
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Session {
     pub exp: DateTime<Utc>,
@@ -8,7 +13,7 @@ pub struct Session {
     pub nbf: DateTime<Utc>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Account {
     pub id: Option<AccountId>,
     pub email: String,
@@ -17,3 +22,54 @@ pub struct Account {
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct AccountId(pub i32);
+
+/// How a stored account logs in
+#[derive(Debug, Clone, PartialEq)]
+pub struct Credentials {
+    pub id: AccountId,
+    pub email: String,
+    /// The password hash. Accounts imported without one have none and
+    /// cannot log in.
+    pub password: Option<String>,
+}
+
+/// The account a post is attributed to, without any private details
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Author {
+    pub id: AccountId,
+    pub display_name: Option<String>,
+}
+
+/// Public profile of an account. It must never contain the email
+/// address, which is only used to log in.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Profile {
+    pub id: AccountId,
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub avatar_url: Option<String>,
+    pub joined_on: DateTime<Utc>,
+    pub questions: Vec<Question>,
+    pub answers: Vec<PostedAnswer>,
+}
+
+/// Body of `PUT /account/profile`, replaces all profile fields
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ProfileUpdate {
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+/// The avatar is a URL, so only the texts are checked
+impl TextFields for ProfileUpdate {
+    fn text_fields(&mut self) -> Vec<(&'static str, &mut String)> {
+        [
+            ("display_name", self.display_name.as_mut()),
+            ("bio", self.bio.as_mut()),
+        ]
+        .into_iter()
+        .filter_map(|(name, text)| text.map(|text| (name, text)))
+        .collect()
+    }
+}
@@ -1,7 +1,32 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    profanity::TextFields,
+    types::{moderation::ModerationStatus, question::QuestionId},
+};
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnswerId(pub i32);
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Answer {
     pub content: String,
     pub question_id: i32,
 }
+
+impl TextFields for Answer {
+    fn text_fields(&mut self) -> Vec<(&'static str, &mut String)> {
+        vec![("content", &mut self.content)]
+    }
+}
+
+/// An answer as it is stored and shown to other users
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PostedAnswer {
+    pub id: AnswerId,
+    pub content: String,
+    pub question_id: QuestionId,
+    pub created_on: DateTime<Utc>,
+    pub moderation_status: ModerationStatus,
+}
@@ -0,0 +1,120 @@
+/// Entity tag of a question, derived from its `version` column
+#[must_use]
+pub fn from_version(version: i32) -> String {
+    format!("\"{version}\"")
+}
+
+/// A parsed `If-Match` or `If-None-Match` header
+#[derive(Debug, PartialEq, Eq)]
+pub enum Precondition {
+    /// `*`, matches every existing version
+    Any,
+    /// The versions named by the listed entity tags
+    Versions(Vec<i32>),
+}
+
+impl Precondition {
+    #[must_use]
+    pub fn matches(&self, version: i32) -> bool {
+        match self {
+            Precondition::Any => true,
+            Precondition::Versions(versions) => versions.contains(&version),
+        }
+    }
+
+    /// The versions an update is allowed to overwrite,
+    /// `None` if any version will do
+    #[must_use]
+    pub fn into_versions(self) -> Option<Vec<i32>> {
+        match self {
+            Precondition::Any => None,
+            Precondition::Versions(versions) => Some(versions),
+        }
+    }
+}
+
+/// Parses the value of an `If-None-Match` header, which compares weakly,
+/// so `W/"3"` matches version 3
+/// # Example usage
+/// ```rust
+/// use warp_rest_api::types::etag::{parse_if_none_match, Precondition};
+///
+/// assert_eq!(parse_if_none_match("*"), Precondition::Any);
+/// assert_eq!(
+///     parse_if_none_match("\"3\", W/\"4\""),
+///     Precondition::Versions(vec![3, 4])
+/// );
+/// ```
+/// Tags which were not issued by us can never match, so they are skipped.
+#[must_use]
+pub fn parse_if_none_match(header: &str) -> Precondition {
+    parse(header, true)
+}
+
+/// Parses the value of an `If-Match` header. It compares strongly
+/// (RFC 7232, section 3.1), so weak tags never match.
+/// # Example usage
+/// ```rust
+/// use warp_rest_api::types::etag::{parse_if_match, Precondition};
+///
+/// assert_eq!(
+///     parse_if_match("\"3\", W/\"4\""),
+///     Precondition::Versions(vec![3])
+/// );
+/// ```
+#[must_use]
+pub fn parse_if_match(header: &str) -> Precondition {
+    parse(header, false)
+}
+
+fn parse(header: &str, weak: bool) -> Precondition {
+    if header.trim() == "*" {
+        return Precondition::Any;
+    }
+
+    Precondition::Versions(
+        header
+            .split(',')
+            .filter_map(|tag| {
+                let tag = tag.trim();
+                let tag = match tag.strip_prefix("W/") {
+                    Some(weak_tag) if weak => weak_tag,
+                    Some(_) => return None,
+                    None => tag,
+                };
+                tag.trim_matches('"').parse().ok()
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod etag_tests {
+    use super::{from_version, parse_if_match, parse_if_none_match, Precondition};
+
+    #[test]
+    fn round_trip() {
+        let precondition = parse_if_match(&from_version(7));
+        assert!(precondition.matches(7));
+        assert!(!precondition.matches(8));
+    }
+
+    #[test]
+    fn wildcard_matches_everything() {
+        assert!(parse_if_match(" * ").matches(42));
+        assert_eq!(parse_if_none_match("*").into_versions(), None);
+    }
+
+    #[test]
+    fn foreign_tags_never_match() {
+        let precondition = parse_if_none_match("\"abc\", \"\"");
+        assert_eq!(precondition, Precondition::Versions(vec![]));
+        assert!(!precondition.matches(1));
+    }
+
+    #[test]
+    fn if_match_skips_weak_tags() {
+        assert!(parse_if_none_match("W/\"2\"").matches(2));
+        assert!(!parse_if_match("W/\"2\"").matches(2));
+    }
+}
@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::types::{
+    account::AccountId, answer::AnswerId, moderation::ModerationStatus, question::QuestionId,
+};
+
+/// One line of an export. Accounts come first, then questions and then
+/// answers, so every record only refers to records before it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Record {
+    Account(AccountRecord),
+    Question(QuestionRecord),
+    Answer(AnswerRecord),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AccountRecord {
+    pub id: AccountId,
+    pub email: String,
+    /// The password hash, only exported on request. Accounts imported
+    /// without one cannot log in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub avatar_url: Option<String>,
+    pub is_moderator: bool,
+    /// Missing in exports from before there were admins
+    #[serde(default)]
+    pub is_admin: bool,
+    pub created_on: DateTime<Utc>,
+}
+
+/// Deleted questions are included, so they can still be restored
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct QuestionRecord {
+    pub id: QuestionId,
+    pub account_id: AccountId,
+    pub title: String,
+    pub content: String,
+    pub tags: Option<Vec<String>>,
+    pub moderation_status: ModerationStatus,
+    pub version: i32,
+    pub created_on: DateTime<Utc>,
+    pub updated_on: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AnswerRecord {
+    pub id: AnswerId,
+    pub question_id: QuestionId,
+    pub account_id: AccountId,
+    pub content: String,
+    pub moderation_status: ModerationStatus,
+    pub created_on: DateTime<Utc>,
+}
+
+/// What an import stored
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Imported {
+    pub accounts: usize,
+    pub questions: usize,
+    pub answers: usize,
+}
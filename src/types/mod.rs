@@ -1,4 +1,8 @@
 pub mod account;
 pub mod answer;
+pub mod etag;
+pub mod export;
+pub mod moderation;
 pub mod pagination;
 pub mod question;
+pub mod report;
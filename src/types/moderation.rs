@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::{answer::PostedAnswer, question::Question};
+
+/// Review state of user content, stored as text in `moderation_status`
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationStatus {
+    Approved,
+    /// Flagged by the content filter and waiting for a review
+    Pending,
+    Rejected,
+}
+
+impl ModerationStatus {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ModerationStatus::Approved => "approved",
+            ModerationStatus::Pending => "pending",
+            ModerationStatus::Rejected => "rejected",
+        }
+    }
+
+    /// Unknown values are treated as not reviewed yet
+    #[must_use]
+    pub fn from_db(status: &str) -> Self {
+        match status {
+            "approved" => ModerationStatus::Approved,
+            "rejected" => ModerationStatus::Rejected,
+            _ => ModerationStatus::Pending,
+        }
+    }
+}
+
+/// Posts which are not approved, either all pending ones for
+/// moderators or the ones of a single author
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModeratedPosts {
+    pub questions: Vec<Question>,
+    pub answers: Vec<PostedAnswer>,
+}
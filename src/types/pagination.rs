@@ -1,5 +1,5 @@
 use handle_errors::Error;
-use std::collections::HashMap;
+use std::{collections::HashMap, hash::BuildHasher};
 
 /// Pagination struct which is getting extract
 /// from query params
@@ -25,28 +25,25 @@ pub struct Pagination {
 /// let mut query = HashMap::new();
 /// query.insert("limit".to_string(), "1".to_string());
 /// query.insert("offset".to_string(), "10".to_string());
-/// let p = extract_pagination(query).unwrap();
+/// let p = extract_pagination(&query).unwrap();
 /// assert_eq!(p.limit, Some(1));
 /// assert_eq!(p.offset, 10);
 /// ```
-pub fn extract_pagination(params: HashMap<String, String>) -> Result<Pagination, Error> {
+///
+/// # Errors
+///
+/// With `MissingParameters` unless both are given, or with `ParseError`
+/// if one is no number
+pub fn extract_pagination<S: BuildHasher>(
+    params: &HashMap<String, String, S>,
+) -> Result<Pagination, Error> {
     // Could be improved in the future
-    if params.contains_key("limit") && params.contains_key("offset") {
+    if let (Some(limit), Some(offset)) = (params.get("limit"), params.get("offset")) {
         return Ok(Pagination {
             // Takes the "limit" parameter in the query and tries to convert it to a number
-            limit: Some(
-                params
-                    .get("limit")
-                    .unwrap()
-                    .parse()
-                    .map_err(Error::ParseError)?,
-            ),
+            limit: Some(limit.parse().map_err(Error::ParseError)?),
             // Takes the "offset" parameter in the query and tries to convert it to a number
-            offset: params
-                .get("offset")
-                .unwrap()
-                .parse()
-                .map_err(Error::ParseError)?,
+            offset: offset.parse().map_err(Error::ParseError)?,
         });
     }
 
@@ -62,7 +59,7 @@ mod pagination_tests {
         let mut params = HashMap::new();
         params.insert(String::from("limit"), String::from("1"));
         params.insert(String::from("offset"), String::from("1"));
-        let pagination_result = extract_pagination(params);
+        let pagination_result = extract_pagination(&params);
         let expected = Pagination {
             limit: Some(1),
             offset: 1,
@@ -75,7 +72,7 @@ mod pagination_tests {
         let mut params = HashMap::new();
         params.insert(String::from("limit"), String::from("1"));
 
-        let pagination_result = format!("{}", extract_pagination(params).unwrap_err());
+        let pagination_result = format!("{}", extract_pagination(&params).unwrap_err());
         let expected = format!("{}", Error::MissingParameters);
 
         assert_eq!(pagination_result, expected);
@@ -86,7 +83,7 @@ mod pagination_tests {
         let mut params = HashMap::new();
         params.insert(String::from("offset"), String::from("1"));
 
-        let pagination_result = format!("{}", extract_pagination(params).unwrap_err());
+        let pagination_result = format!("{}", extract_pagination(&params).unwrap_err());
         let expected = format!("{}", Error::MissingParameters);
 
         assert_eq!(pagination_result, expected);
@@ -97,7 +94,7 @@ mod pagination_tests {
         let mut params = HashMap::new();
         params.insert(String::from("limit"), String::from("1"));
         params.insert(String::from("offset"), String::from("NOT_A_NUMBER"));
-        let pagination_result = format!("{}", extract_pagination(params).unwrap_err());
+        let pagination_result = format!("{}", extract_pagination(&params).unwrap_err());
 
         let expected = String::from("Cannot parse parameter: invalid digit found in string");
 
@@ -109,7 +106,7 @@ mod pagination_tests {
         let mut params = HashMap::new();
         params.insert(String::from("limit"), String::from("NOT_A_NUMBER"));
         params.insert(String::from("offset"), String::from("1"));
-        let pagination_result = format!("{}", extract_pagination(params).unwrap_err());
+        let pagination_result = format!("{}", extract_pagination(&params).unwrap_err());
 
         let expected = String::from("Cannot parse parameter: invalid digit found in string");
 
@@ -1,6 +1,12 @@
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::fmt;
 
+use crate::{
+    profanity::TextFields,
+    types::{account::Author, moderation::ModerationStatus},
+};
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct QuestionId(pub i32);
 
@@ -10,12 +16,19 @@ impl fmt::Display for QuestionId {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Question {
     pub id: QuestionId,
     pub title: String,
     pub content: String,
     pub tags: Option<Vec<String>>,
+    pub author: Author,
+    pub created_on: DateTime<Utc>,
+    pub updated_on: DateTime<Utc>,
+    pub moderation_status: ModerationStatus,
+    /// Incremented on every update, sent to clients as the `ETag`
+    #[serde(skip)]
+    pub version: i32,
 }
 
 impl fmt::Display for Question {
@@ -34,3 +47,45 @@ pub struct NewQuestion {
     pub content: String,
     pub tags: Option<Vec<String>>,
 }
+
+/// Body of `PATCH /questions/{id}`, absent fields are left untouched
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct QuestionPatch {
+    pub title: Option<String>,
+    pub content: Option<String>,
+    /// `Some(None)` for `"tags": null`, which removes the tags
+    #[serde(
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tags: Option<Option<Vec<String>>>,
+}
+
+/// Only called for fields in the body, so an explicit `null` becomes
+/// `Some(None)` while an absent field stays `None`
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+impl TextFields for NewQuestion {
+    fn text_fields(&mut self) -> Vec<(&'static str, &mut String)> {
+        vec![("title", &mut self.title), ("content", &mut self.content)]
+    }
+}
+
+impl TextFields for QuestionPatch {
+    fn text_fields(&mut self) -> Vec<(&'static str, &mut String)> {
+        [
+            ("title", self.title.as_mut()),
+            ("content", self.content.as_mut()),
+        ]
+        .into_iter()
+        .filter_map(|(name, text)| text.map(|text| (name, text)))
+        .collect()
+    }
+}
@@ -0,0 +1,146 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::types::{
+    account::AccountId, answer::AnswerId, moderation::ModerationStatus, question::QuestionId,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct ReportId(pub i32);
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportReason {
+    Spam,
+    Abuse,
+    OffTopic,
+    Other,
+}
+
+impl ReportReason {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReportReason::Spam => "spam",
+            ReportReason::Abuse => "abuse",
+            ReportReason::OffTopic => "off_topic",
+            ReportReason::Other => "other",
+        }
+    }
+
+    #[must_use]
+    pub fn from_db(reason: &str) -> Self {
+        match reason {
+            "spam" => ReportReason::Spam,
+            "abuse" => ReportReason::Abuse,
+            "off_topic" => ReportReason::OffTopic,
+            _ => ReportReason::Other,
+        }
+    }
+}
+
+/// What a moderator decided about a reported post
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportAction {
+    /// The post is fine and gets approved
+    Keep,
+    /// The post gets rejected
+    Remove,
+}
+
+impl ReportAction {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReportAction::Keep => "keep",
+            ReportAction::Remove => "remove",
+        }
+    }
+
+    /// Status the reported post gets
+    #[must_use]
+    pub fn status(self) -> ModerationStatus {
+        match self {
+            ReportAction::Keep => ModerationStatus::Approved,
+            ReportAction::Remove => ModerationStatus::Rejected,
+        }
+    }
+
+    #[must_use]
+    pub fn from_db(action: &str) -> Option<Self> {
+        match action {
+            "keep" => Some(ReportAction::Keep),
+            "remove" => Some(ReportAction::Remove),
+            _ => None,
+        }
+    }
+}
+
+/// Longest note a reporter can leave for the moderators
+pub const MAX_NOTE_LENGTH: usize = 1000;
+
+/// Open reports after which a post is hidden until a moderator
+/// reviewed it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReportThreshold(pub i64);
+
+impl Default for ReportThreshold {
+    fn default() -> Self {
+        ReportThreshold(3)
+    }
+}
+
+/// Body of `POST /questions/{id}/report` and `POST /answers/{id}/report`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NewReport {
+    pub reason: ReportReason,
+    pub note: Option<String>,
+}
+
+/// The reported post, either a question or an answer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportTarget {
+    Question(i32),
+    Answer(i32),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    pub id: ReportId,
+    pub reporter: AccountId,
+    pub question_id: Option<QuestionId>,
+    pub answer_id: Option<AnswerId>,
+    pub reason: ReportReason,
+    pub note: Option<String>,
+    pub created_on: DateTime<Utc>,
+    pub resolved_on: Option<DateTime<Utc>>,
+    pub resolution: Option<ReportAction>,
+}
+
+/// Body of `POST /moderation/reports/{id}/resolve`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReportResolution {
+    pub action: ReportAction,
+}
+
+#[cfg(test)]
+mod report_tests {
+    use super::{ReportAction, ReportReason};
+
+    #[test]
+    fn stored_values_round_trip() {
+        for reason in [
+            ReportReason::Spam,
+            ReportReason::Abuse,
+            ReportReason::OffTopic,
+            ReportReason::Other,
+        ] {
+            assert_eq!(ReportReason::from_db(reason.as_str()), reason);
+        }
+
+        for action in [ReportAction::Keep, ReportAction::Remove] {
+            assert_eq!(ReportAction::from_db(action.as_str()), Some(action));
+        }
+    }
+}